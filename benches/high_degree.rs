@@ -0,0 +1,39 @@
+// Benchmark of the node transitions at a vertex of high in- and out-degree.
+// Every source s_i is connected to the hub h and the hub to every sink t_j,
+// and packets of every pair (s_i, t_j) are released over time, so all of them merge at h.
+// Run with `cargo bench`. For a comparison with the former routing, which rescanned all leaving queues
+// of the vertex for every outgoing edge, run the same file on the commit before the single pass routing.
+use std::time::Instant;
+use routing::{NetworkBuilder, Network, Fraction};
+
+fn hub_network(degree : usize, packets_per_pair : usize) -> Network{
+    let mut builder = NetworkBuilder::new();
+    for i in 0..degree{
+        builder = builder.edge(&format!("s{}", i), "h", 1, Fraction::new(degree as i64, 1));
+    }
+    for j in 0..degree{
+        builder = builder.edge("h", &format!("t{}", j), 1, Fraction::new(degree as i64, 1));
+    }
+    for k in 0..packets_per_pair{
+        for i in 0..degree{
+            for j in 0..degree{
+                builder = builder.packet(i, k, &[&format!("s{}", i), "h", &format!("t{}", j)]);
+            }
+        }
+    }
+    builder.build().0
+}
+
+fn main(){
+    for degree in [10, 20, 40, 80, 160]{
+        let packets_per_pair = 8;
+        let mut network = hub_network(degree, packets_per_pair);
+        let start = Instant::now();
+        network.run_simulation();
+        let elapsed = start.elapsed();
+        println!(
+            "in-degree = out-degree = {:3}, {:6} packets, {:5} time steps: {:10.3} ms",
+            degree, network.topology.packets.len(), network.state.time, elapsed.as_secs_f64() * 1000.0
+        );
+    }
+}
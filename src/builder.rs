@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+
+use crate::commodity::{ReleasePattern, split_packets};
+use crate::fraction::Fraction;
+use crate::network::{Network, Topology, SimulationConfig, Vertex, Edge, Packet, VertexId, EdgeId, CommodityId, Time};
+use crate::read_json::{EdgeToId, vertex_path_to_edge_path};
+use crate::schedule::Schedule;
+
+/// Builds a network in memory instead of reading it from network.json and packets.json.
+/// Vertices are referred to by name and get their ids in order of first appearance,
+/// exactly as in read_json::map_vertex_name_id.
+///
+/// ```
+/// use routing::{NetworkBuilder, Fraction};
+/// let (mut network, _vertex_id_to_name) = NetworkBuilder::new()
+///     .edge("s", "t", 1, Fraction::new(1, 1))
+///     .packet(0, 0, &["s", "t"])
+///     .build();
+/// network.run_simulation();
+/// assert_eq!(network.state.arrival_times[0], Some(1));
+/// ```
+#[derive(Default)]
+pub struct NetworkBuilder{
+    vertex_name_to_id : HashMap<String, VertexId>,
+    vertex_id_to_name : HashMap<VertexId, String>,
+    vertices : Vec<Vertex>,
+    edges : Vec<Edge>,
+    edge_to_id : EdgeToId,
+    packets : Vec<Packet>,
+    priority_classes : HashMap<CommodityId, usize>,
+    config : SimulationConfig,
+}
+
+impl NetworkBuilder{
+    pub fn new() -> Self{
+        Self::default()
+    }
+
+    // Return the id of the vertex with the given name, adding the vertex if it is new
+    fn vertex_id(&mut self, name : &str) -> VertexId{
+        if let Some(id) = self.vertex_name_to_id.get(name){
+            return *id;
+        }
+        let new_id = self.vertices.len();
+        self.vertex_name_to_id.insert(name.to_string(), new_id);
+        self.vertex_id_to_name.insert(new_id, name.to_string());
+        self.vertices.push(Vertex{incoming_edges : Vec::new(), outgoing_edges : Vec::new(), capacity : None});
+        new_id
+    }
+
+    /// Add an edge from v_from to v_to with the given transit time and capacity
+    pub fn edge(mut self, v_from : &str, v_to : &str, transit_time : usize, capacity : Fraction) -> Self{
+        assert!(transit_time > 0, "edge lengths should be positive");
+        assert!(capacity.numerator > 0, "edge capacities should be positive");
+        let v_from = self.vertex_id(v_from);
+        let v_to = self.vertex_id(v_to);
+        let edge_id = self.edges.len();
+        self.edges.push(
+            Edge{
+                id : edge_id,
+                v_from,
+                v_to,
+                length : Schedule::constant(transit_time),
+                capacity : Schedule::constant(capacity),
+                storage : None,
+                inflow_capacity : None,
+                buffer : None,
+            }
+        );
+        self.vertices[v_from].outgoing_edges.push(edge_id);
+        self.vertices[v_to].incoming_edges.push(edge_id);
+        self.edge_to_id.insert((v_from, v_to), edge_id);
+        self
+    }
+
+    /// Replace the capacity of the last added edge by a piecewise constant capacity,
+    /// given as (starting time, capacity) pairs beginning at time 0
+    pub fn capacity_schedule(mut self, pieces : Vec<(Time, Fraction)>) -> Self{
+        self.edges.last_mut().expect("add an edge first").capacity = Schedule::new(pieces);
+        self
+    }
+
+    /// Replace the transit time of the last added edge by a piecewise constant transit time
+    /// depending on the entrance time, given as (starting time, transit time) pairs beginning at time 0
+    pub fn transit_time_schedule(mut self, pieces : Vec<(Time, usize)>) -> Self{
+        assert!(pieces.iter().all(|(_, transit_time)| *transit_time > 0), "edge lengths should be positive");
+        self.edges.last_mut().expect("add an edge first").length = Schedule::new(pieces);
+        self
+    }
+
+    /// Limit the number of packets on the last added edge, packets wait in front of a full edge
+    pub fn storage(mut self, storage : usize) -> Self{
+        assert!(storage > 0, "edge storages should be positive");
+        self.edges.last_mut().expect("add an edge first").storage = Some(storage);
+        self
+    }
+
+    /// Limit the average number of packets entering the last added edge per time step
+    pub fn inflow_capacity(mut self, inflow_capacity : Fraction) -> Self{
+        assert!(inflow_capacity.numerator > 0, "edge inflow capacities should be positive");
+        self.edges.last_mut().expect("add an edge first").inflow_capacity = Some(inflow_capacity);
+        self
+    }
+
+    /// Give the last added edge a finite buffer, packets entering it beyond the buffer are lost
+    /// according to the drop policy of the config
+    pub fn buffer(mut self, buffer : usize) -> Self{
+        assert!(buffer > 0, "edge buffers should be positive");
+        self.edges.last_mut().expect("add an edge first").buffer = Some(buffer);
+        self
+    }
+
+    /// Limit the average number of packets passing the vertex with the given name per time step
+    pub fn vertex_capacity(mut self, vertex : &str, capacity : Fraction) -> Self{
+        assert!(capacity.numerator > 0, "vertex capacities should be positive");
+        let vertex_id = self.vertex_name_to_id[vertex];
+        self.vertices[vertex_id].capacity = Some(capacity);
+        self
+    }
+
+    /// Add a packet of the given commodity which is released at release_time and follows the vertex path
+    pub fn packet(mut self, commodity_id : CommodityId, release_time : Time, path : &[&str]) -> Self{
+        assert!(path.len() >= 2, "paths should have length at least 2");
+        let vertex_path : Vec<VertexId> = path.iter().map(|name| self.vertex_name_to_id[*name]).collect();
+        let packet_id = self.packets.len();
+        self.packets.push(
+            Packet{
+                id : packet_id,
+                commodity_id,
+                release_time,
+                source : vertex_path[0],
+                destination : *vertex_path.last().unwrap(),
+                path : vertex_path_to_edge_path(vertex_path, &self.edge_to_id),
+                size : Fraction::new(1, 1),
+                priority_class : 0,
+            }
+        );
+        self
+    }
+
+    /// Add a packet of the given commodity which is released at release_time at the source
+    /// and chooses its edges to the destination by the routing policy of the config
+    pub fn adaptive_packet(mut self, commodity_id : CommodityId, release_time : Time, source : &str, destination : &str) -> Self{
+        let packet_id = self.packets.len();
+        self.packets.push(
+            Packet{
+                id : packet_id,
+                commodity_id,
+                release_time,
+                path : Vec::new(),
+                source : self.vertex_name_to_id[source],
+                destination : self.vertex_name_to_id[destination],
+                size : Fraction::new(1, 1),
+                priority_class : 0,
+            }
+        );
+        self
+    }
+
+    /// Replace the unit size of the last added packet, it takes this part of the capacity of an edge when leaving it
+    pub fn size(mut self, size : Fraction) -> Self{
+        assert!(size.numerator > 0, "packet sizes should be positive");
+        self.packets.last_mut().expect("add a packet first").size = size;
+        self
+    }
+
+    /// Add the packets of a commodity released by the pattern and split over the vertex paths, given with their
+    /// split ratios, such that the realized split tracks the ratios over time, see commodity::split_packets
+    pub fn split_commodity(mut self, commodity_id : CommodityId, release_pattern : ReleasePattern, paths : &[(&[&str], Fraction)]) -> Self{
+        assert!(!paths.is_empty(), "commodities should have at least one path");
+        let mut edge_paths = Vec::<Vec<EdgeId>>::new();
+        for (path, _) in paths{
+            assert!(path.len() >= 2, "paths should have length at least 2");
+            assert!(path[0] == paths[0].0[0] && path.last() == paths[0].0.last(), "paths of a commodity should have the same source and sink");
+            let vertex_path : Vec<VertexId> = path.iter().map(|name| self.vertex_name_to_id[*name]).collect();
+            edge_paths.push(vertex_path_to_edge_path(vertex_path, &self.edge_to_id));
+        }
+        let source = self.vertex_name_to_id[paths[0].0[0]];
+        let sink = self.vertex_name_to_id[*paths[0].0.last().unwrap()];
+        let split_ratios : Vec<Fraction> = paths.iter().map(|(_, ratio)| *ratio).collect();
+        let first_packet_id = self.packets.len();
+        self.packets.extend(split_packets(commodity_id, source, sink, &release_pattern, &edge_paths, &split_ratios, first_packet_id));
+        self
+    }
+
+    /// Put all packets of the commodity, also those added later, into the priority class
+    pub fn priority_class(mut self, commodity_id : CommodityId, priority_class : usize) -> Self{
+        self.priority_classes.insert(commodity_id, priority_class);
+        self
+    }
+
+    /// Set the options for simulating the network
+    pub fn config(mut self, config : SimulationConfig) -> Self{
+        self.config = config;
+        self
+    }
+
+    /// Finish building, returning the network and the map from vertex ids to names
+    pub fn build(mut self) -> (Network, HashMap<VertexId, String>){
+        for packet in &mut self.packets{
+            packet.priority_class = self.priority_classes.get(&packet.commodity_id).copied().unwrap_or(0);
+        }
+        let mut topology = Topology::new(self.vertices, self.edges, self.packets);
+        topology.config = self.config;
+        (Network::new(topology), self.vertex_id_to_name)
+    }
+}
@@ -0,0 +1,143 @@
+use std::{fs::File, io::{Read, Write}};
+use std::collections::VecDeque;
+use serde_json::json;
+use serde_json::to_string_pretty;
+
+use crate::fraction::Fraction;
+use crate::network::{Topology, SimulationState, Time, EdgeId, PacketId};
+use crate::read_json::get_fraction;
+
+fn fraction_val(fraction : &Fraction) -> serde_json::Value{
+    json!({"numerator": fraction.numerator, "denominator": fraction.denominator})
+}
+
+// The complete simulation state, from which the simulation can be resumed with identical results
+// given the same topology and config
+pub fn get_checkpoint_val(state : &SimulationState) -> serde_json::Value{
+    json!({
+        "time": state.time,
+        "edge_queues": state.edge_queues,
+        "leaving_queues": state.leaving_queues,
+        "source_queues": state.source_queues,
+        "current_capacities": state.current_capacities.iter().map(fraction_val).collect::<Vec<serde_json::Value>>(),
+        "inflow_credits": state.inflow_credits.iter().map(fraction_val).collect::<Vec<serde_json::Value>>(),
+        "vertex_credits": state.vertex_credits.iter().map(fraction_val).collect::<Vec<serde_json::Value>>(),
+        "entrance_times": state.entrance_times,
+        "path_positions": state.path_positions,
+        "routed_paths": state.routed_paths,
+        "arrival_times": state.arrival_times,
+        "drops": state.drops,
+    })
+}
+
+fn get_queues(queues_val : &serde_json::Value) -> Vec<VecDeque<PacketId>>{
+    queues_val.as_array().unwrap().iter()
+        .map(|queue_val| queue_val.as_array().unwrap().iter().map(|packet_id_val| packet_id_val.as_u64().unwrap() as PacketId).collect())
+        .collect()
+}
+
+fn get_options(options_val : &serde_json::Value) -> Vec<Option<usize>>{
+    options_val.as_array().unwrap().iter().map(|option_val| option_val.as_u64().map(|value| value as usize)).collect()
+}
+
+// Read a simulation state of the topology. Missing entries are taken from the initial state at time 0,
+// so an initial state with packets already on edges only needs e.g. "time", "edge_queues",
+// "entrance_times" and "path_positions".
+pub fn get_state(topology : &Topology, checkpoint_val : &serde_json::Value) -> SimulationState{
+    let mut state = SimulationState::new(topology);
+    if let Some(time) = checkpoint_val["time"].as_u64(){
+        state.time = time as Time;
+    }
+    if !checkpoint_val["edge_queues"].is_null(){
+        state.edge_queues = get_queues(&checkpoint_val["edge_queues"]);
+    }
+    if !checkpoint_val["leaving_queues"].is_null(){
+        state.leaving_queues = get_queues(&checkpoint_val["leaving_queues"]);
+    }
+    if !checkpoint_val["source_queues"].is_null(){
+        state.source_queues = get_queues(&checkpoint_val["source_queues"]);
+    }
+    if let Some(capacities_val) = checkpoint_val["current_capacities"].as_array(){
+        state.current_capacities = capacities_val.iter().map(get_fraction).collect();
+    }
+    if let Some(credits_val) = checkpoint_val["inflow_credits"].as_array(){
+        state.inflow_credits = credits_val.iter().map(get_fraction).collect();
+    }
+    if let Some(credits_val) = checkpoint_val["vertex_credits"].as_array(){
+        state.vertex_credits = credits_val.iter().map(get_fraction).collect();
+    }
+    if !checkpoint_val["entrance_times"].is_null(){
+        state.entrance_times = get_options(&checkpoint_val["entrance_times"]);
+    }
+    if !checkpoint_val["path_positions"].is_null(){
+        state.path_positions = get_options(&checkpoint_val["path_positions"]);
+    }
+    if let Some(paths_val) = checkpoint_val["routed_paths"].as_array(){
+        state.routed_paths = paths_val.iter()
+            .map(|path_val| path_val.as_array().unwrap().iter().map(|edge_id_val| edge_id_val.as_u64().unwrap() as EdgeId).collect())
+            .collect();
+    }
+    if !checkpoint_val["arrival_times"].is_null(){
+        state.arrival_times = get_options(&checkpoint_val["arrival_times"]);
+    }
+    if let Some(drops_val) = checkpoint_val["drops"].as_array(){
+        state.drops = drops_val.iter()
+            .map(|drop_val| drop_val.as_array().map(|pair_val| (pair_val[0].as_u64().unwrap() as Time, pair_val[1].as_u64().unwrap() as EdgeId)))
+            .collect();
+    }
+    state.packets_arrived = state.arrival_times.iter().filter(|arrival_time| arrival_time.is_some()).count();
+    state.packets_dropped = state.drops.iter().filter(|drop| drop.is_some()).count();
+    check_state(topology, &state);
+    state
+}
+
+// Assert that the state is one the simulation of the topology can continue from
+pub fn check_state(topology : &Topology, state : &SimulationState){
+    let n_packets = topology.packets.len();
+    assert!(state.edge_queues.len() == topology.edges.len() && state.leaving_queues.len() == topology.edges.len() && state.source_queues.len() == topology.edges.len(), "Error: the state should have queues for every edge");
+    assert!(state.current_capacities.len() == topology.edges.len() && state.inflow_credits.len() == topology.edges.len(), "Error: the state should have credits for every edge");
+    assert_eq!(state.vertex_credits.len(), topology.vertices.len(), "Error: the state should have a credit for every vertex");
+    assert!([state.entrance_times.len(), state.path_positions.len(), state.routed_paths.len(), state.arrival_times.len(), state.drops.len()].iter().all(|length| *length == n_packets), "Error: the state should have an entry for every packet");
+    let mut n_occurrences = vec![0; n_packets];
+    for (edge_id, queue) in state.edge_queues.iter().enumerate().chain(state.leaving_queues.iter().enumerate()){
+        for packet_id in queue{
+            assert!(*packet_id < n_packets, "Error: unknown packet id {}", packet_id);
+            n_occurrences[*packet_id] += 1;
+            let path_position = state.path_positions[*packet_id].unwrap_or_else(|| panic!("Error: packet {} on an edge should have a path position", packet_id));
+            assert_eq!(state.path(topology, *packet_id).get(path_position), Some(&edge_id), "Error: packet {} should be on the edge at its path position", packet_id);
+            assert!(state.entrance_times[*packet_id].is_some(), "Error: packet {} on an edge should have an entrance time", packet_id);
+        }
+    }
+    for (edge_id, source_queue) in state.source_queues.iter().enumerate(){
+        for packet_id in source_queue{
+            assert!(*packet_id < n_packets, "Error: unknown packet id {}", packet_id);
+            n_occurrences[*packet_id] += 1;
+            assert!(state.path_positions[*packet_id].is_none(), "Error: waiting packet {} should not have a path position", packet_id);
+            assert_eq!(state.path(topology, *packet_id).first(), Some(&edge_id), "Error: packet {} should wait for the first edge of its path", packet_id);
+        }
+    }
+    for packet in &topology.packets{
+        let finished = state.arrival_times[packet.id].is_some() || state.drops[packet.id].is_some();
+        assert!(n_occurrences[packet.id] <= 1 && (n_occurrences[packet.id] == 0 || !finished), "Error: packet {} should be in at most one queue and only if it has neither arrived nor been dropped", packet.id);
+        assert!(state.path_positions[packet.id].is_none() || n_occurrences[packet.id] == 1 || finished, "Error: packet {} with a path position should be on an edge", packet.id);
+        // packets are released into the network during the time step of their release time
+        match n_occurrences[packet.id] == 1 || finished{
+            true => assert!(packet.release_time < state.time, "Error: packet {} should not be in the network before its release", packet.id),
+            false => assert!(packet.release_time >= state.time, "Error: released packet {} should be in the network", packet.id),
+        }
+    }
+}
+
+pub fn write_checkpoint(state : &SimulationState, checkpoint_fname : &str){
+    let checkpoint_val = get_checkpoint_val(state);
+    let mut checkpoint_json = File::create(checkpoint_fname).unwrap();
+    checkpoint_json.write_all(to_string_pretty(&checkpoint_val).unwrap().as_bytes()).expect("Failed to write checkpoint to json");
+}
+
+pub fn read_checkpoint(topology : &Topology, checkpoint_fname : &str) -> SimulationState{
+    let mut checkpoint_json = File::open(checkpoint_fname).unwrap();
+    let mut checkpoint_string = String::new();
+    checkpoint_json.read_to_string(&mut checkpoint_string).unwrap();
+    let checkpoint_val : serde_json::Value = serde_json::from_str(&checkpoint_string).unwrap();
+    get_state(topology, &checkpoint_val)
+}
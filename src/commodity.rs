@@ -0,0 +1,75 @@
+use crate::fraction::Fraction;
+use crate::network::{Packet, Time, VertexId, EdgeId, PacketId, CommodityId};
+
+// When the packets of a commodity are released
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReleasePattern{
+    // one packet per entry, several packets may be released at the same time
+    Times(Vec<Time>),
+    // constant inflow rate in packets per time step during [from, until), a packet is released
+    // whenever the cumulative inflow reaches the next integer
+    Rate{rate : Fraction, from : Time, until : Time},
+}
+
+impl ReleasePattern{
+    // Release times of all packets in increasing order
+    pub fn release_times(&self) -> Vec<Time>{
+        match self{
+            ReleasePattern::Times(times) => {
+                let mut times = times.clone();
+                times.sort_unstable();
+                times
+            }
+            ReleasePattern::Rate{rate, from, until} => {
+                assert!(rate.numerator >= 0 && rate.denominator > 0, "Error: release rates should be non-negative");
+                let cumulative = |time : Time| ((time - from) as i64 * rate.numerator / rate.denominator) as usize;
+                (*from..*until).flat_map(|time| std::iter::repeat_n(time, cumulative(time + 1) - cumulative(time))).collect()
+            }
+        }
+    }
+}
+
+// Assign n_packets packets in order to paths with the given split ratios such that after every packet the number
+// of packets on each path deviates as little as possible from its share: each packet takes the path which is
+// furthest behind its share, ties are broken by the smaller path index
+pub fn split_assignment(split_ratios : &[Fraction], n_packets : usize) -> Vec<usize>{
+    assert!(!split_ratios.is_empty(), "Error: there should be at least one split ratio");
+    assert!(split_ratios.iter().all(|ratio| ratio.numerator >= 0 && ratio.denominator > 0), "Error: split ratios should be non-negative");
+    let total = split_ratios.iter().fold(Fraction::new(0, 1), |sum, ratio| sum + *ratio);
+    assert!(total == Fraction::new(1, 1), "Error: split ratios should sum up to 1");
+    let mut n_assigned = vec![0i64; split_ratios.len()];
+    let mut assignment = Vec::<usize>::with_capacity(n_packets);
+    for n_packets_so_far in 1..=n_packets as i64{
+        // share of the path after this packet minus the packets already on it, as numerator and denominator
+        // since it may be negative
+        let deficit = |path_index : usize| {
+            let ratio = split_ratios[path_index];
+            (ratio.numerator * n_packets_so_far - n_assigned[path_index] * ratio.denominator, ratio.denominator)
+        };
+        let path_index = (0..split_ratios.len()).rev().max_by(|index_a, index_b| {
+            let ((numerator_a, denominator_a), (numerator_b, denominator_b)) = (deficit(*index_a), deficit(*index_b));
+            (numerator_a * denominator_b).cmp(&(numerator_b * denominator_a))
+        }).unwrap();
+        n_assigned[path_index] += 1;
+        assignment.push(path_index);
+    }
+    assignment
+}
+
+// Packets of unit size of a commodity released by the pattern and split over the paths by split_assignment,
+// with consecutive ids starting at first_packet_id
+pub fn split_packets(commodity_id : CommodityId, source : VertexId, sink : VertexId, release_pattern : &ReleasePattern, paths : &[Vec<EdgeId>], split_ratios : &[Fraction], first_packet_id : PacketId) -> Vec<Packet>{
+    assert_eq!(paths.len(), split_ratios.len(), "Error: every path should have a split ratio");
+    let release_times = release_pattern.release_times();
+    let assignment = split_assignment(split_ratios, release_times.len());
+    release_times.iter().zip(assignment).enumerate().map(|(i, (release_time, path_index))| Packet{
+        id : first_packet_id + i,
+        commodity_id,
+        release_time : *release_time,
+        path : paths[path_index].clone(),
+        source,
+        destination : sink,
+        size : Fraction::new(1, 1),
+        priority_class : 0,
+    }).collect()
+}
@@ -0,0 +1,282 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::fraction::Fraction;
+use crate::network::{Topology, SimulationState, Time, VertexId, EdgeId, PacketId, packets_in_order, passing_order};
+
+// Why a packet can never move again
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StuckReason{
+    // its edge has capacity zero from now on
+    ClosedEdge,
+    // the next edge on its path does not leave the vertex it waits at
+    InconsistentPath,
+    // it waits for the given edge, which is full of stuck packets
+    FullEdge(EdgeId),
+    // it waits behind a packet which never leaves the edge queue or never enters its next edge
+    BehindStuckPacket,
+    // it is larger than its edge can ever serve in one time step, see ServiceModel::max_service
+    TooLarge,
+    // it is larger than the inflow capacity of the given edge ever admits in one time step
+    InflowLimit(EdgeId),
+    // it is larger than the capacity of the vertex it waits at ever lets pass in one time step
+    VertexLimit,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StuckPacket{
+    pub packet_id : PacketId,
+    pub edge_id : Option<EdgeId>, // edge the packet is on, None if it waits to enter the network
+    pub vertex_id : VertexId, // vertex the packet waits at
+    pub reason : StuckReason,
+}
+
+// Description of a simulation state in which no packet can ever move again
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeadlockReport{
+    pub time : Time, // time of detection, after the first time step in which no packet moved
+    pub stuck_packets : Vec<StuckPacket>, // ordered by packet id
+    pub edges : Vec<EdgeId>, // edges the stuck packets are on or wait for
+    pub vertices : Vec<VertexId>, // vertices the stuck packets wait at
+    pub waiting_cycle : Vec<EdgeId>, // full edges each waiting for the next one and the last for the first, empty if there is no such cycle
+}
+
+impl fmt::Display for StuckReason{
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result{
+        match self{
+            StuckReason::ClosedEdge => write!(f, "its edge is closed"),
+            StuckReason::InconsistentPath => write!(f, "its path does not continue at the vertex"),
+            StuckReason::FullEdge(edge_id) => write!(f, "edge {} is full", edge_id),
+            StuckReason::BehindStuckPacket => write!(f, "it waits behind a stuck packet"),
+            StuckReason::TooLarge => write!(f, "it is too large for its edge"),
+            StuckReason::InflowLimit(edge_id) => write!(f, "it is too large for the inflow capacity of edge {}", edge_id),
+            StuckReason::VertexLimit => write!(f, "it is too large for the capacity of its vertex"),
+        }
+    }
+}
+
+impl fmt::Display for DeadlockReport{
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result{
+        writeln!(f, "Deadlock at time {}: {} packets can never move again", self.time, self.stuck_packets.len())?;
+        for stuck_packet in &self.stuck_packets{
+            match stuck_packet.edge_id{
+                Some(edge_id) => write!(f, "  packet {} on edge {}", stuck_packet.packet_id, edge_id)?,
+                None => write!(f, "  packet {} waiting to enter the network", stuck_packet.packet_id)?,
+            }
+            writeln!(f, " at vertex {}: {}", stuck_packet.vertex_id, stuck_packet.reason)?;
+        }
+        writeln!(f, "Edges involved: {:?}", self.edges)?;
+        write!(f, "Vertices involved: {:?}", self.vertices)?;
+        if !self.waiting_cycle.is_empty(){
+            let cycle : Vec<String> = self.waiting_cycle.iter().chain(self.waiting_cycle.first()).map(|edge_id| edge_id.to_string()).collect();
+            write!(f, "\nWaiting cycle of edges: {}", cycle.join(" -> "))?;
+        }
+        Ok(())
+    }
+}
+
+// Packets on the edge in the order in which they leave it, blocked packets first
+fn packets_on_edge(state : &SimulationState, edge_id : EdgeId) -> impl Iterator<Item = &PacketId>{
+    state.leaving_queues[edge_id].iter().chain(state.edge_queues[edge_id].iter())
+}
+
+// Give the first packet in the order which is larger than a limit can ever serve in one time step the given reason,
+// the packets after it wait behind it forever. Reasons found for an earlier limit are kept.
+fn mark_limited(topology : &Topology, order : impl Iterator<Item = PacketId>, credit : Fraction, max_service : Option<Fraction>, reason : StuckReason, reasons : &mut [Option<StuckReason>]){
+    let mut stuck = false;
+    for packet_id in order{
+        let size = topology.packets[packet_id].size;
+        if stuck{
+            reasons[packet_id].get_or_insert(StuckReason::BehindStuckPacket);
+        }
+        else if credit < size && max_service.is_some_and(|max_service| max_service < size){
+            reasons[packet_id].get_or_insert(reason);
+            stuck = true;
+        }
+    }
+}
+
+// Determine for every packet waiting at a vertex whether it never gets past the capacity of the vertex
+// or the inflow capacity of its next edge.
+// The limits admit a prefix of the packets in their order in every time step, which keeps the queues of every incoming edge FIFO.
+fn limited_reasons(topology : &Topology, state : &SimulationState) -> Vec<Option<StuckReason>>{
+    let mut reasons = vec![None; topology.packets.len()];
+    for (vertex_id, vertex) in topology.vertices.iter().enumerate(){
+        if vertex.outgoing_edges.is_empty(){
+            continue;
+        }
+        let (incoming_queues, mut passing_queues) = state.waiting_queues(topology, vertex_id);
+        if let Some(capacity) = vertex.capacity{
+            let order = passing_order(topology, vertex_id, &mut passing_queues);
+            let max_service = topology.config.service_model.max_service(capacity);
+            mark_limited(topology, packets_in_order(&passing_queues, &order), state.vertex_credits[vertex_id], max_service, StuckReason::VertexLimit, &mut reasons);
+        }
+        for (queues, outgoing_edge_id) in incoming_queues.iter().zip(&vertex.outgoing_edges){
+            if let Some(inflow_capacity) = topology.edges[*outgoing_edge_id].inflow_capacity{
+                let order = state.merge_order(topology, vertex_id, *outgoing_edge_id, queues);
+                let max_service = topology.config.service_model.max_service(inflow_capacity);
+                mark_limited(topology, packets_in_order(queues, &order), state.inflow_credits[*outgoing_edge_id], max_service, StuckReason::InflowLimit(*outgoing_edge_id), &mut reasons);
+            }
+        }
+    }
+    reasons
+}
+
+// Determine for every packet whether it is stuck, assuming that the blocked edges never admit a packet again.
+// The service models are assumed to serve a ready packet eventually while the capacity is positive unless it is larger
+// than their max_service. Blocked packets have already been served and only wait for their next edge,
+// unless they are given a reason by limited_reasons.
+fn stuck_reasons(topology : &Topology, state : &SimulationState, blocked : &[bool], limited : &[Option<StuckReason>]) -> Vec<Option<StuckReason>>{
+    let mut reasons = vec![None; topology.packets.len()];
+    for edge in &topology.edges{
+        let credit = state.current_capacities[edge.id];
+        let max_capacity = edge.capacity.max_from(state.time + 1);
+        let closing = max_capacity == Fraction::new(0, 1);
+        let max_service = topology.config.service_model.max_service(max_capacity);
+        // without capacity from now on only the packets at the front fitting into the credit together still leave
+        let mut remaining_credit = credit;
+        let mut closed_in_front = false;
+        // whether a packet in front never leaves the edge queue
+        let mut too_large_in_front = false;
+        let n_blocked = state.leaving_queues[edge.id].len();
+        for (position, packet_id) in packets_on_edge(state, edge.id).enumerate(){
+            let next_position = state.path_positions[*packet_id].unwrap() + 1;
+            let size = topology.packets[*packet_id].size;
+            let served = position < n_blocked;
+            let closed = !served && closing && (closed_in_front || remaining_credit < size);
+            if closing && !served && !closed{
+                remaining_credit = remaining_credit - size;
+            }
+            closed_in_front |= closed;
+            let too_large = !served && !too_large_in_front && credit < size && max_service.is_some_and(|max_service| max_service < size);
+            let reason = if closed{
+                Some(StuckReason::ClosedEdge)
+            }
+            else if too_large{
+                too_large_in_front = true;
+                Some(StuckReason::TooLarge)
+            }
+            else if too_large_in_front{
+                Some(StuckReason::BehindStuckPacket)
+            }
+            else{
+                match state.path(topology, *packet_id).get(next_position){
+                    None => None,
+                    Some(next_edge_id) if topology.vertices[edge.v_to].outgoing_edges.binary_search(next_edge_id).is_err() => Some(StuckReason::InconsistentPath),
+                    Some(next_edge_id) if blocked[*next_edge_id] => Some(StuckReason::FullEdge(*next_edge_id)),
+                    Some(_) => limited[*packet_id],
+                }
+            };
+            reasons[*packet_id] = reason;
+        }
+    }
+    for (edge_id, source_queue) in state.source_queues.iter().enumerate(){
+        for packet_id in source_queue{
+            reasons[*packet_id] = match blocked[edge_id]{
+                true => Some(StuckReason::FullEdge(edge_id)),
+                false => limited[*packet_id],
+            };
+        }
+    }
+    reasons
+}
+
+// Find a cycle in the graph in which every edge points to the edges it waits for
+fn find_waiting_cycle(waits_for : &[BTreeSet<EdgeId>]) -> Vec<EdgeId>{
+    // 0: not visited, 1: on the current path, 2: finished without finding a cycle
+    let mut marks = vec![0; waits_for.len()];
+    let mut path = Vec::<EdgeId>::new();
+    for edge_id in 0..waits_for.len(){
+        if let Some(cycle) = visit(edge_id, waits_for, &mut marks, &mut path){
+            return cycle;
+        }
+    }
+    Vec::new()
+}
+
+fn visit(edge_id : EdgeId, waits_for : &[BTreeSet<EdgeId>], marks : &mut Vec<u8>, path : &mut Vec<EdgeId>) -> Option<Vec<EdgeId>>{
+    match marks[edge_id]{
+        1 => {
+            let start = path.iter().position(|path_edge_id| *path_edge_id == edge_id).unwrap();
+            return Some(path[start..].to_vec());
+        }
+        2 => return None,
+        _ => {}
+    }
+    marks[edge_id] = 1;
+    path.push(edge_id);
+    for next_edge_id in &waits_for[edge_id]{
+        if let Some(cycle) = visit(*next_edge_id, waits_for, marks, path){
+            return Some(cycle);
+        }
+    }
+    path.pop();
+    marks[edge_id] = 2;
+    None
+}
+
+// Report a deadlock if no packet which has neither arrived nor been dropped yet can ever move again.
+// Deadlocks are only detected once all packets are released and have completed their transit.
+pub fn detect_deadlock(topology : &Topology, state : &SimulationState) -> Option<DeadlockReport>{
+    if state.is_finished(topology) || topology.release_schedule.next_release_time(state.time).is_some(){
+        return None;
+    }
+    for (edge_id, edge_queue) in state.edge_queues.iter().enumerate(){
+        if edge_queue.iter().any(|packet_id| topology.edges[edge_id].leaving_time(state.entrance_times[*packet_id].unwrap()) > state.time){
+            return None;
+        }
+    }
+    // greatest fixpoint: start with all full edges blocked and unblock those holding a packet which is not stuck
+    let mut blocked : Vec<bool> = topology.edges.iter().map(|edge| edge.free_storage(packets_on_edge(state, edge.id).count()) == Some(0)).collect();
+    let limited = limited_reasons(topology, state);
+    let reasons = loop{
+        let reasons = stuck_reasons(topology, state, &blocked, &limited);
+        let mut changed = false;
+        for (edge_id, edge_blocked) in blocked.iter_mut().enumerate(){
+            if *edge_blocked && packets_on_edge(state, edge_id).any(|packet_id| reasons[*packet_id].is_none()){
+                *edge_blocked = false;
+                changed = true;
+            }
+        }
+        if !changed{
+            break reasons;
+        }
+    };
+    if (0..topology.packets.len()).any(|packet_id| state.arrival_times[packet_id].is_none() && state.drops[packet_id].is_none() && reasons[packet_id].is_none()){
+        return None;
+    }
+
+    let mut stuck_packets = Vec::<StuckPacket>::new();
+    let mut waits_for = vec![BTreeSet::<EdgeId>::new(); topology.edges.len()];
+    for edge in &topology.edges{
+        for packet_id in packets_on_edge(state, edge.id){
+            let reason = reasons[*packet_id].unwrap();
+            if let StuckReason::FullEdge(next_edge_id) = reason{
+                waits_for[edge.id].insert(next_edge_id);
+            }
+            stuck_packets.push(StuckPacket{packet_id : *packet_id, edge_id : Some(edge.id), vertex_id : edge.v_to, reason});
+        }
+    }
+    for (edge_id, source_queue) in state.source_queues.iter().enumerate(){
+        for packet_id in source_queue{
+            stuck_packets.push(StuckPacket{packet_id : *packet_id, edge_id : None, vertex_id : topology.edges[edge_id].v_from, reason : reasons[*packet_id].unwrap()});
+        }
+    }
+    stuck_packets.sort_by_key(|stuck_packet| stuck_packet.packet_id);
+    let mut edges = BTreeSet::<EdgeId>::new();
+    let mut vertices = BTreeSet::<VertexId>::new();
+    for stuck_packet in &stuck_packets{
+        edges.extend(stuck_packet.edge_id);
+        if let StuckReason::FullEdge(next_edge_id) | StuckReason::InflowLimit(next_edge_id) = stuck_packet.reason{
+            edges.insert(next_edge_id);
+        }
+        vertices.insert(stuck_packet.vertex_id);
+    }
+    Some(DeadlockReport{
+        time : state.time,
+        stuck_packets,
+        edges : edges.into_iter().collect(),
+        vertices : vertices.into_iter().collect(),
+        waiting_cycle : find_waiting_cycle(&waits_for),
+    })
+}
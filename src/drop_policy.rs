@@ -0,0 +1,85 @@
+use std::sync::Arc;
+
+use crate::network::{Time, EdgeId, PacketId};
+use crate::random::SplitMix64;
+
+// What happens to a packet entering an edge with a finite buffer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DropDecision{
+    Admit,
+    // the entering packet is lost
+    DropArriving,
+    // the packet which entered the edge first is lost and the entering packet takes its place,
+    // blocked packets have entered before those still in the edge queue
+    DropOldest,
+}
+
+// Rule deciding which packets are lost at an edge whose buffer limits the number of packets on it
+pub trait DropPolicy : Send + Sync{
+    // Decide about the packet entering the edge at the given time while n_packets packets are on the edge
+    fn decide(&self, packet_id : PacketId, edge_id : EdgeId, time : Time, n_packets : usize, buffer : usize) -> DropDecision;
+}
+
+// Packets entering a full edge are lost
+pub struct TailDrop;
+
+impl DropPolicy for TailDrop{
+    fn decide(&self, _packet_id : PacketId, _edge_id : EdgeId, _time : Time, n_packets : usize, buffer : usize) -> DropDecision{
+        match n_packets < buffer{
+            true => DropDecision::Admit,
+            false => DropDecision::DropArriving,
+        }
+    }
+}
+
+// A packet entering a full edge replaces the packet which has been on the edge the longest
+pub struct DropOldest;
+
+impl DropPolicy for DropOldest{
+    fn decide(&self, _packet_id : PacketId, _edge_id : EdgeId, _time : Time, n_packets : usize, buffer : usize) -> DropDecision{
+        match n_packets < buffer{
+            true => DropDecision::Admit,
+            false => DropDecision::DropOldest,
+        }
+    }
+}
+
+// Random early detection: while at most half of the buffer is used every packet is admitted, beyond that
+// an entering packet is lost with probability growing linearly with the number of packets on the edge
+// up to 1 for a full edge. Packets entering an empty edge are always admitted. The decisions are reproducible for a given seed.
+pub struct RandomEarlyDrop{
+    pub seed : u64,
+}
+
+impl DropPolicy for RandomEarlyDrop{
+    fn decide(&self, packet_id : PacketId, edge_id : EdgeId, time : Time, n_packets : usize, buffer : usize) -> DropDecision{
+        let threshold = buffer / 2;
+        if n_packets <= threshold || n_packets == 0{
+            return DropDecision::Admit;
+        }
+        if n_packets >= buffer{
+            return DropDecision::DropArriving;
+        }
+        // with threshold < n_packets < buffer the probability is (n_packets - threshold) / (buffer - threshold)
+        let mut generator = SplitMix64::from_values(self.seed, &[time as u64, edge_id as u64, packet_id as u64]);
+        match generator.next_below((buffer - threshold) as u64) < (n_packets - threshold) as u64{
+            true => DropDecision::DropArriving,
+            false => DropDecision::Admit,
+        }
+    }
+}
+
+// Parse a drop policy name as used in network.json and on the command line:
+// tail_drop, drop_oldest or random_early[:seed]
+pub fn drop_policy_from_name(name : &str) -> Option<Arc<dyn DropPolicy>>{
+    match name.split_once(':'){
+        Some(("random_early", seed)) => Some(Arc::new(RandomEarlyDrop{seed : seed.parse().ok()?})),
+        Some(_) => None,
+        None => match name{
+            "tail_drop" => Some(Arc::new(TailDrop)),
+            "drop_oldest" => Some(Arc::new(DropOldest)),
+            "random_early" => Some(Arc::new(RandomEarlyDrop{seed : 0})),
+            _ => None,
+        },
+    }
+}
@@ -0,0 +1,136 @@
+use crate::network::{Topology, SimulationState, Time, VertexId, EdgeId, PacketId, CommodityId};
+use crate::release_schedule::ReleaseSchedule;
+
+// Packets of a commodity travel from its source to its sink on one of its candidate paths
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Commodity{
+    pub id : CommodityId,
+    pub source : VertexId,
+    pub sink : VertexId,
+    pub candidate_paths : Vec<Vec<EdgeId>>,
+}
+
+// Outcome of solve_equilibrium
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EquilibriumResult{
+    // whether no packet could improve its arrival time in the last round
+    pub converged : bool,
+    // number of rounds in which every packet got the chance to change its path
+    pub rounds : usize,
+    // number of times a packet changed its path
+    pub path_changes : usize,
+    // final path of every packet
+    pub paths : Vec<Vec<EdgeId>>,
+    // arrival times with the final paths, None for packets which never arrive
+    pub arrival_times : Vec<Option<Time>>,
+}
+
+// All paths from source to sink which visit no vertex twice, in depth first order along the outgoing edges
+pub fn simple_paths(topology : &Topology, source : VertexId, sink : VertexId) -> Vec<Vec<EdgeId>>{
+    let mut paths = Vec::<Vec<EdgeId>>::new();
+    let mut visited = vec![false; topology.vertices.len()];
+    let mut path = Vec::<EdgeId>::new();
+    extend_simple_paths(topology, source, sink, &mut visited, &mut path, &mut paths);
+    paths
+}
+
+fn extend_simple_paths(topology : &Topology, vertex_id : VertexId, sink : VertexId, visited : &mut Vec<bool>, path : &mut Vec<EdgeId>, paths : &mut Vec<Vec<EdgeId>>){
+    if vertex_id == sink{
+        paths.push(path.clone());
+        return;
+    }
+    visited[vertex_id] = true;
+    for edge_id in &topology.vertices[vertex_id].outgoing_edges{
+        let v_to = topology.edges[*edge_id].v_to;
+        if !visited[v_to]{
+            path.push(*edge_id);
+            extend_simple_paths(topology, v_to, sink, visited, path, paths);
+            path.pop();
+        }
+    }
+    visited[vertex_id] = false;
+}
+
+// Arrival time of the packet when simulating the topology from scratch, None if it is dropped or deadlocked
+fn simulated_arrival_time(topology : &Topology, packet_id : PacketId) -> Option<Time>{
+    let mut state = SimulationState::new(topology);
+    state.run_until_arrived(topology, packet_id, &mut ());
+    state.arrival_times[packet_id]
+}
+
+fn set_path(topology : &mut Topology, packet_id : PacketId, path : &[EdgeId]){
+    topology.packets[packet_id].path = path.to_vec();
+    topology.release_schedule = ReleaseSchedule::new(&topology.packets);
+}
+
+// Never arriving is worse than arriving at any time
+fn cost(arrival_time : Option<Time>) -> Time{
+    arrival_time.unwrap_or(Time::MAX)
+}
+
+// Search a dynamic equilibrium by iterated best responses: in every round the packets of the commodities
+// one after another switch to the candidate path with the earliest arrival time, given the current paths
+// of all other packets, if it arrives strictly earlier than the current path. Packets start on their given path
+// if it is a candidate and on the first candidate otherwise. The search converges once a round changes no path
+// and stops unconverged after max_rounds rounds. The final paths are stored in the packets of the topology.
+pub fn solve_equilibrium(topology : &mut Topology, commodities : &[Commodity], max_rounds : usize) -> EquilibriumResult{
+    let mut packet_candidates = vec![None; topology.packets.len()];
+    for commodity in commodities{
+        assert!(!commodity.candidate_paths.is_empty(), "Error: commodity {} should have a candidate path", commodity.id);
+        for path in &commodity.candidate_paths{
+            assert!(!path.is_empty(), "Error: candidate paths should not be empty");
+            assert_eq!(topology.edges[path[0]].v_from, commodity.source, "Error: candidate paths should start at the source");
+            assert_eq!(topology.edges[*path.last().unwrap()].v_to, commodity.sink, "Error: candidate paths should end at the sink");
+            assert!(path.windows(2).all(|pair| topology.edges[pair[0]].v_to == topology.edges[pair[1]].v_from), "Error: candidate paths should be connected");
+        }
+        for packet in topology.packets.iter_mut().filter(|packet| packet.commodity_id == commodity.id){
+            assert!(packet.source == commodity.source && packet.destination == commodity.sink, "Error: packet {} should travel from the source to the sink of its commodity", packet.id);
+            if !commodity.candidate_paths.contains(&packet.path){
+                packet.path = commodity.candidate_paths[0].clone();
+            }
+            packet_candidates[packet.id] = Some(&commodity.candidate_paths);
+        }
+    }
+    topology.release_schedule = ReleaseSchedule::new(&topology.packets);
+
+    let mut converged = false;
+    let mut rounds = 0;
+    let mut path_changes = 0;
+    while !converged && rounds < max_rounds{
+        rounds += 1;
+        converged = true;
+        for (packet_id, candidate_paths) in packet_candidates.iter().enumerate(){
+            let candidate_paths = match candidate_paths{
+                Some(candidate_paths) => candidate_paths,
+                None => continue,
+            };
+            let current_path = topology.packets[packet_id].path.clone();
+            let mut best_cost = cost(simulated_arrival_time(topology, packet_id));
+            let mut best_path = &current_path;
+            for path in candidate_paths.iter().filter(|path| **path != current_path){
+                set_path(topology, packet_id, path);
+                let path_cost = cost(simulated_arrival_time(topology, packet_id));
+                if path_cost < best_cost{
+                    best_cost = path_cost;
+                    best_path = path;
+                }
+            }
+            #[cfg(debug_assertions)]
+            println!("Packet {} best responds with path {:?}", packet_id, best_path);
+            if *best_path != current_path{
+                converged = false;
+                path_changes += 1;
+            }
+            set_path(topology, packet_id, best_path);
+        }
+    }
+    let mut state = SimulationState::new(topology);
+    state.run_simulation(topology);
+    EquilibriumResult{
+        converged,
+        rounds,
+        path_changes,
+        paths : topology.packets.iter().map(|packet| packet.path.clone()).collect(),
+        arrival_times : state.arrival_times,
+    }
+}
@@ -1,6 +1,7 @@
 use std::cmp::Ordering;
 use std::{cmp, fmt};
 use std::ops::{Add, Sub};
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Copy)]
 pub struct Fraction {
@@ -28,7 +29,7 @@ impl Fraction {
 
     // Return the Fraction rounded down to the nearest integer
     pub fn floor(&self) -> Self {
-        return Fraction{numerator : self.numerator / self.denominator, denominator : 1};
+        Fraction{numerator : self.numerator / self.denominator, denominator : 1}
     }
 }
 
@@ -43,6 +44,23 @@ impl fmt::Display for Fraction {
     }
 }
 
+// Parse "n" or "n/d", e.g. from the command line
+impl FromStr for Fraction {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let parse = |part: &str| part.trim().parse::<i64>().map_err(|error| format!("invalid fraction {}: {}", string, error));
+        let (numerator, denominator) = match string.split_once('/') {
+            Some((numerator, denominator)) => (parse(numerator)?, parse(denominator)?),
+            None => (parse(string)?, 1),
+        };
+        if numerator < 0 || denominator <= 0 {
+            return Err(format!("invalid fraction {}: must be non-negative", string));
+        }
+        Ok(Fraction::new(numerator, denominator))
+    }
+}
+
 impl cmp::PartialEq for Fraction {
     fn eq(&self, other: &Fraction) -> bool {
         let simp_self = self.reduce();
@@ -57,26 +75,26 @@ impl cmp::Eq for Fraction {}
 
 impl cmp::PartialOrd for Fraction {
     fn partial_cmp(&self, other: &Fraction) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl cmp::Ord for Fraction {
+    fn cmp(&self, other: &Fraction) -> Ordering {
         assert!(self.numerator < i32::MAX as i64 && self.denominator < i32::MAX as i64);
         assert!(other.numerator < i32::MAX as i64 && other.denominator < i32::MAX as i64);
         if self.eq(other){
-            return Some(Ordering::Equal);
+            Ordering::Equal
         }
         else{
             match self.numerator * other.denominator < other.numerator * self.denominator{
-                true => Some(Ordering::Less),
-                false => Some(Ordering::Greater),
+                true => Ordering::Less,
+                false => Ordering::Greater,
             }
         }
     }
 }
 
-impl cmp::Ord for Fraction {
-    fn cmp(&self, other: &Fraction) -> Ordering {
-        self.partial_cmp(other).unwrap()
-    }
-}
-
 impl Add for Fraction {
     type Output = Fraction;
 
@@ -106,10 +124,10 @@ impl Sub for Fraction {
 // Calculate the greatest common denominator for two numbers
 pub fn gcd(a: i64, b: i64) -> i64 {
     if b == 0{
-        return a;
+        a
     }
     else{
-        return gcd(b, a % b);
+        gcd(b, a % b)
     }
 }
 
@@ -133,6 +151,15 @@ fn equality_test() {
     assert!(a != c);
 }
 
+#[test]
+fn parse_test() {
+    assert_eq!("3".parse::<Fraction>(), Ok(Fraction::new(3, 1)));
+    assert_eq!("5/2".parse::<Fraction>(), Ok(Fraction::new(5, 2)));
+    assert!("1/0".parse::<Fraction>().is_err());
+    assert!("-1".parse::<Fraction>().is_err());
+    assert!("a/2".parse::<Fraction>().is_err());
+}
+
 #[test]
 fn arithmetic_test() {
     let a = Fraction::new(1, 2);
@@ -13,32 +13,26 @@ pub struct MaxHeapElement{
 
 impl cmp::PartialOrd for MaxHeapElement{
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for MaxHeapElement{
+    fn cmp(&self, other : &MaxHeapElement) -> Ordering{
         assert_ne!(self.edge_id, other.edge_id, "Error: edge ids should never be equal");
         assert_ne!(self.queue_id, other.queue_id, "Error: queue ids should never be equal");
         if self.priority != other.priority{
-            // higher priority means less, i.e. first
-            // TODO: check, test
+            // smaller priority value means greater, i.e. popped first
             match self.priority < other.priority {
-                true => Some(Ordering::Greater),
-                false => Some(Ordering::Less),
+                true => Ordering::Greater,
+                false => Ordering::Less,
             }
         }
         else{
             match self.edge_id < other.edge_id{
-                true => Some(Ordering::Greater),
-                false => Some(Ordering::Less), 
+                true => Ordering::Greater,
+                false => Ordering::Less, 
             }
         }
     }
-}
-
-impl Ord for MaxHeapElement{
-    fn cmp(&self, other : &MaxHeapElement) -> Ordering{
-        let ord = self.partial_cmp(other).unwrap();
-        match ord {
-            Ordering::Greater => Ordering::Less,
-            Ordering::Less => Ordering::Greater,
-            Ordering::Equal => ord
-        }
-    }
 }
\ No newline at end of file
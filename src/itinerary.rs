@@ -0,0 +1,68 @@
+use crate::network::{Topology, SimulationState, Time, EdgeId, PacketId};
+use crate::observer::Observer;
+
+// Passage of a packet over one edge of its path
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EdgeVisit{
+    pub edge_id : EdgeId,
+    // time the packet entered the edge queue
+    pub entrance_time : Time,
+    // time the packet completed its transit and was eligible to leave, i.e. entrance_time + length
+    pub eligible_time : Time,
+    // time the packet actually left, None while it is still on the edge or if it was dropped on it
+    pub leaving_time : Option<Time>,
+}
+
+impl EdgeVisit{
+    // Time spent in transit on the edge
+    pub fn free_flow_time(&self) -> Time{
+        self.eligible_time - self.entrance_time
+    }
+
+    // Time spent waiting after completing the transit, None if the packet did not leave
+    pub fn waiting_time(&self) -> Option<Time>{
+        self.leaving_time.map(|leaving_time| leaving_time - self.eligible_time)
+    }
+}
+
+// Observer recording the edge visits of every packet in the order of its path
+pub struct ItineraryRecorder<'a>{
+    topology : &'a Topology,
+    pub itineraries : Vec<Vec<EdgeVisit>>,
+}
+
+impl<'a> ItineraryRecorder<'a>{
+    // Start recording at the given state, packets already on an edge start with their visit of it
+    pub fn new(topology : &'a Topology, state : &SimulationState) -> Self{
+        let mut itineraries = vec![Vec::<EdgeVisit>::new(); topology.packets.len()];
+        for (edge_id, queue) in state.edge_queues.iter().enumerate().chain(state.leaving_queues.iter().enumerate()){
+            for packet_id in queue{
+                let entrance_time = state.entrance_times[*packet_id].unwrap();
+                itineraries[*packet_id].push(EdgeVisit{
+                    edge_id,
+                    entrance_time,
+                    eligible_time : topology.edges[edge_id].leaving_time(entrance_time),
+                    leaving_time : None,
+                });
+            }
+        }
+        ItineraryRecorder{topology, itineraries}
+    }
+}
+
+impl Observer for ItineraryRecorder<'_>{
+    fn packet_entered_edge(&mut self, packet_id : PacketId, edge_id : EdgeId, time : Time){
+        self.itineraries[packet_id].push(EdgeVisit{
+            edge_id,
+            entrance_time : time,
+            eligible_time : self.topology.edges[edge_id].leaving_time(time),
+            leaving_time : None,
+        });
+    }
+
+    fn packet_left_edge(&mut self, packet_id : PacketId, edge_id : EdgeId, time : Time){
+        let visit = self.itineraries[packet_id].last_mut().expect("Error: a packet can only leave an edge it entered");
+        assert_eq!(visit.edge_id, edge_id, "Error: packet {} left an edge it is not on", packet_id);
+        visit.leaving_time = Some(time);
+    }
+}
@@ -0,0 +1,60 @@
+//! Discrete packet routing model: packets travel along edges with transit times and
+//! capacities and merge at vertices according to a `MergePolicy`, by default the zipper method.
+//!
+//! Instances are either read from `network.json` and `packets.json` (see `read_json`)
+//! or built in memory with `NetworkBuilder`. After `Network::run_simulation` the results
+//! can be written to `results.json` (see `write_json`).
+//!
+//! - `network`: a `Network` consists of the static `Topology` and the `SimulationState`, which
+//!   can be reset or cloned to rerun the instance, or advanced step by step while an
+//!   `Observer` is notified of every packet movement.
+//! - `deadlock`: if no packet can ever move again, e.g. because edges with finite storage
+//!   block each other, the simulation stops with a `DeadlockReport`.
+//! - `statistics`: with a time horizon the simulation may stop unfinished, `statistics::summary`
+//!   tells where the pending packets are and `statistics::class_statistics` summarizes the
+//!   results per priority class.
+//! - `checkpoint`: saves the state of a stopped simulation to resume it later with identical
+//!   results, or reads a given initial state.
+//! - `itinerary`: an `ItineraryRecorder` observes the times at which every packet entered,
+//!   could leave and left each edge.
+//! - `queue_lengths`: a `QueueLengthRecorder` observes the numbers of queued packets on every
+//!   edge over time.
+//! - `routing_policy`: packets given only a source and a destination choose their edges by a
+//!   `RoutingPolicy`, e.g. as in an instantaneous dynamic equilibrium.
+//! - `commodity`: commodities release packets by a `ReleasePattern` and split them over several
+//!   paths by ratios; their priority class is served first by the class_priority merge policy.
+//! - `equilibrium`: `solve_equilibrium` searches paths for the packets of commodities with
+//!   candidate paths such that no packet can arrive earlier by switching its path.
+pub mod network;
+pub mod fraction;
+pub mod heap_element;
+pub mod read_json;
+pub mod write_json;
+pub mod builder;
+pub mod observer;
+pub mod release_schedule;
+pub mod merge_policy;
+pub mod service_model;
+pub mod random;
+pub mod schedule;
+pub mod deadlock;
+pub mod drop_policy;
+pub mod routing_policy;
+pub mod equilibrium;
+pub mod commodity;
+pub mod statistics;
+pub mod checkpoint;
+pub mod itinerary;
+pub mod queue_lengths;
+mod tests;
+
+pub use network::{Network, Topology, SimulationState, SimulationConfig, Vertex, Edge, Packet, Time, VertexId, EdgeId, PacketId, CommodityId};
+pub use fraction::Fraction;
+pub use builder::NetworkBuilder;
+pub use observer::Observer;
+pub use merge_policy::MergePolicy;
+pub use service_model::ServiceModel;
+pub use deadlock::DeadlockReport;
+pub use drop_policy::DropPolicy;
+pub use routing_policy::RoutingPolicy;
+pub use commodity::ReleasePattern;
@@ -1,31 +1,191 @@
 use std::env;
-mod network;
-mod fraction;
-mod heap_element;
-mod read_json;
-mod write_json;
-mod tests;
+use routing::{read_json, write_json, statistics, checkpoint};
+use routing::merge_policy::merge_policy_from_name;
+use routing::service_model::service_model_from_name;
+use routing::drop_policy::drop_policy_from_name;
+use routing::routing_policy::routing_policy_from_name;
+use routing::equilibrium::solve_equilibrium;
+use routing::itinerary::ItineraryRecorder;
+use routing::queue_lengths::QueueLengthRecorder;
+
+fn print_usage(){
+    println!("Usage: routing <instance directory> [options]");
+    println!("Options:");
+    println!("  --event-driven          skip time steps in which no packet can move");
+    println!("  --merge-policy <name>   zipper (default), fifo, commodity_priority, class_priority,");
+    println!("                          round_robin, capacity_weighted_zipper or random[:seed]");
+    println!("  --service-model <name>  carry_over (default), no_carry_over, periodic");
+    println!("                          or token_bucket:<burst>");
+    println!("  --drop-policy <name>    tail_drop (default), drop_oldest or random_early[:seed]");
+    println!("  --routing-policy <name> free_flow (default), queue_aware or ide (instantaneous dynamic");
+    println!("                          equilibrium), for packets without a path");
+    println!("  --time-horizon <time>   stop the simulation at this time, also if packets have not arrived");
+    println!("  --equilibrium <rounds>  choose the paths of the packets of the commodities in packets.json");
+    println!("                          by at most this many rounds of best responses");
+    println!("  --itineraries           write the entrance, eligible and leaving time of every packet on each edge");
+    println!("  --queue-lengths         write the numbers of queued packets and of all packets on every edge");
+    println!("                          at the end of every time step");
+    println!("  --queue-length-changes  as --queue-lengths, but only the time steps in which they change");
+    println!("  --resume <file>         continue the simulation from the state in the checkpoint file");
+    println!("  --checkpoint <file>     write the state at which the simulation stopped to the file");
+}
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    match args.len() {
+    if args.len() == 1 {
         // no arguments passed
-        1 => {
-            println!("Please pass directory where packet routing instance is located");
-        },
-        // one argument passed
-        2 => {
-            let mut instance_directory : String = args[1].parse().unwrap();
-            if !instance_directory.ends_with("/"){
-                instance_directory = instance_directory + "/";
-            }
-            let (mut network, vertex_id_to_name) = read_json::read_jsons(&instance_directory);
-            network.run_simulation();
-            write_json::write_json(&network, vertex_id_to_name, &(instance_directory.to_owned() + "results.json"));
+        println!("Please pass directory where packet routing instance is located");
+        print_usage();
+        return;
+    }
+    let mut instance_directory : String = args[1].parse().unwrap();
+    if !instance_directory.ends_with('/'){
+        instance_directory += "/";
+    }
+    let (mut network, vertex_id_to_name, commodities) = read_json::read_jsons_with_commodities(&instance_directory);
+    let mut equilibrium_rounds = None;
+    let mut resume_fname = None;
+    let mut checkpoint_fname = None;
+    let mut record_itineraries = false;
+    // whether to record only changes if queue lengths are recorded
+    let mut queue_length_changes = None;
+    let mut options = args[2..].iter();
+    while let Some(option) = options.next(){
+        match option.as_str(){
+            "--event-driven" => network.topology.config.event_driven = true,
+            "--merge-policy" => {
+                match options.next().and_then(|name| merge_policy_from_name(name)){
+                    Some(merge_policy) => network.topology.config.merge_policy = merge_policy,
+                    None => {
+                        println!("Missing or unknown merge policy");
+                        print_usage();
+                        return;
+                    }
+                }
+            }
+            "--service-model" => {
+                match options.next().and_then(|name| service_model_from_name(name)){
+                    Some(service_model) => network.topology.config.service_model = service_model,
+                    None => {
+                        println!("Missing or unknown service model");
+                        print_usage();
+                        return;
+                    }
+                }
+            }
+            "--drop-policy" => {
+                match options.next().and_then(|name| drop_policy_from_name(name)){
+                    Some(drop_policy) => network.topology.config.drop_policy = drop_policy,
+                    None => {
+                        println!("Missing or unknown drop policy");
+                        print_usage();
+                        return;
+                    }
+                }
+            }
+            "--routing-policy" => {
+                match options.next().and_then(|name| routing_policy_from_name(name)){
+                    Some(routing_policy) => network.topology.config.routing_policy = routing_policy,
+                    None => {
+                        println!("Missing or unknown routing policy");
+                        print_usage();
+                        return;
+                    }
+                }
+            }
+            "--time-horizon" => {
+                match options.next().and_then(|time| time.parse::<usize>().ok()){
+                    Some(time_horizon) => network.topology.config.time_horizon = Some(time_horizon),
+                    None => {
+                        println!("Missing or invalid time horizon");
+                        print_usage();
+                        return;
+                    }
+                }
+            }
+            "--equilibrium" => {
+                match options.next().and_then(|rounds| rounds.parse::<usize>().ok()){
+                    Some(rounds) => equilibrium_rounds = Some(rounds),
+                    None => {
+                        println!("Missing or invalid number of equilibrium rounds");
+                        print_usage();
+                        return;
+                    }
+                }
+            }
+            "--itineraries" => record_itineraries = true,
+            "--queue-lengths" => queue_length_changes = Some(false),
+            "--queue-length-changes" => queue_length_changes = Some(true),
+            "--resume" => {
+                match options.next(){
+                    Some(fname) => resume_fname = Some(fname),
+                    None => {
+                        println!("Missing checkpoint file to resume from");
+                        print_usage();
+                        return;
+                    }
+                }
+            }
+            "--checkpoint" => {
+                match options.next(){
+                    Some(fname) => checkpoint_fname = Some(fname),
+                    None => {
+                        println!("Missing checkpoint file");
+                        print_usage();
+                        return;
+                    }
+                }
+            }
+            _ => {
+                println!("Unknown argument {}", option);
+                print_usage();
+                return;
+            }
+        }
+    }
+    let equilibrium = equilibrium_rounds.map(|max_rounds| solve_equilibrium(&mut network.topology, &commodities, max_rounds));
+    // the initial state depends on the options
+    network.reset();
+    if let Some(fname) = resume_fname{
+        network.state = checkpoint::read_checkpoint(&network.topology, fname);
+    }
+    let itinerary_recorder = match record_itineraries{
+        true => Some(ItineraryRecorder::new(&network.topology, &network.state)),
+        false => None,
+    };
+    let queue_length_recorder = queue_length_changes.map(|only_changes| QueueLengthRecorder::new(&network.topology, only_changes));
+    let mut recorders = (itinerary_recorder, queue_length_recorder);
+    let report = network.state.run_until(&network.topology, network.topology.config.time_horizon.unwrap_or(usize::MAX), &mut recorders);
+    if let Some(fname) = checkpoint_fname{
+        checkpoint::write_checkpoint(&network.state, fname);
+    }
+    match &report{
+        Some(report) => println!("{}", report),
+        None if !network.state.is_finished(&network.topology) => {
+            let summary = statistics::summary(&network.topology, &network.state);
+            println!("Time horizon {} reached with {} of {} packets pending", summary.time, summary.n_pending(), summary.n_packets);
         }
-        // too many arguments passed
-        _ => {
-            println!("Too many arguments passed");
+        None => {}
+    }
+    let results_fname = instance_directory.to_owned() + "results.json";
+    // partial results if the simulation stopped unfinished
+    let mut output_val = write_json::get_output_val(&network.topology, &network.state, vertex_id_to_name.clone());
+    if let Some(report) = report{
+        output_val["deadlock"] = write_json::get_deadlock_val(&network.topology, &report, &vertex_id_to_name);
+    }
+    if let Some(result) = equilibrium{
+        match result.converged{
+            true => println!("Equilibrium found after {} rounds with {} path changes", result.rounds, result.path_changes),
+            false => println!("No equilibrium found within {} rounds", result.rounds),
         }
+        output_val["equilibrium"] = write_json::get_equilibrium_val(&result);
+    }
+    let (itinerary_recorder, queue_length_recorder) = recorders;
+    if let Some(recorder) = itinerary_recorder{
+        output_val["itineraries"] = write_json::get_itineraries_val(&network.topology, &recorder.itineraries, &vertex_id_to_name);
+    }
+    if let Some(recorder) = queue_length_recorder{
+        output_val["queue_lengths"] = write_json::get_queue_lengths_val(&network.topology, &recorder.series, &vertex_id_to_name);
     }
-}
\ No newline at end of file
+    write_json::write_output_val(&output_val, &results_fname);
+}
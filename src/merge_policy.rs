@@ -0,0 +1,254 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::sync::Arc;
+
+use crate::fraction::Fraction;
+use crate::heap_element::MaxHeapElement;
+use crate::network::{Topology, SimulationState, Time, VertexId, EdgeId, PacketId};
+use crate::random::SplitMix64;
+
+// Everything a merge policy may look at when merging packets into an outgoing edge
+pub struct MergeContext<'a>{
+    pub topology : &'a Topology,
+    pub state : &'a SimulationState,
+    pub vertex_id : VertexId,
+    pub outgoing_edge_id : EdgeId,
+    // edge of the i-th incoming queue, None for the queue of packets entering the network
+    pub incoming_edge_ids : &'a [Option<EdgeId>],
+}
+
+impl MergeContext<'_>{
+    // Edge id used for breaking ties, packets entering the network come last
+    pub fn tie_break_id(&self, queue_id : usize) -> EdgeId{
+        self.incoming_edge_ids[queue_id].unwrap_or(EdgeId::MAX)
+    }
+
+    // Time at which the packet became ready to leave its incoming queue
+    pub fn ready_time(&self, queue_id : usize, packet_id : PacketId) -> Time{
+        match self.incoming_edge_ids[queue_id]{
+            Some(edge_id) => self.topology.edges[edge_id].leaving_time(self.state.entrance_times[packet_id].unwrap()),
+            None => self.topology.packets[packet_id].release_time,
+        }
+    }
+}
+
+// Rule deciding in which order the packets of the incoming queues of a vertex enter an outgoing edge
+pub trait MergePolicy : Send + Sync{
+    // Return queue ids such that the i-th entry is the queue whose front packet enters the outgoing edge i-th.
+    // Every queue id must appear exactly as often as the queue has packets, so each queue stays FIFO.
+    fn merge_order(&self, context : &MergeContext, incoming_queues : &[VecDeque<PacketId>]) -> Vec<usize>;
+}
+
+// Repeatedly pick the non-empty queue with the smallest key of its front packet, ties broken by the queue order
+fn merge_by_front_key<K : Ord>(incoming_queues : &[VecDeque<PacketId>], mut key : impl FnMut(usize, PacketId) -> K) -> Vec<usize>{
+    let mut positions = vec![0; incoming_queues.len()];
+    let n_packets : usize = incoming_queues.iter().map(|queue| queue.len()).sum();
+    let mut order = Vec::<usize>::with_capacity(n_packets);
+    while order.len() < n_packets{
+        let queue_id = (0..incoming_queues.len())
+            .filter(|queue_id| positions[*queue_id] < incoming_queues[*queue_id].len())
+            .min_by_key(|queue_id| key(*queue_id, incoming_queues[*queue_id][positions[*queue_id]]))
+            .unwrap();
+        positions[queue_id] += 1;
+        order.push(queue_id);
+    }
+    order
+}
+
+// Zipper where a packet has priority s / weight, with s the total size of the packets of its queue up to and including it
+// and the weight of a queue given by the closure. For unit sizes the k-th packet of a queue has priority k / weight.
+// Smaller priorities go first, ties are broken by the smaller tie break id.
+fn weighted_zipper(incoming_queues : &[VecDeque<PacketId>], tie_break_id : impl Fn(usize) -> EdgeId, size : impl Fn(PacketId) -> Fraction, weight : impl Fn(usize) -> Fraction) -> Vec<usize>{
+    let mut priority_queue = BinaryHeap::<MaxHeapElement>::new();
+    let mut positions = vec![0; incoming_queues.len()];
+    let mut cumulative_sizes = vec![Fraction::new(0, 1); incoming_queues.len()];
+    let mut order = Vec::<usize>::new();
+    let weights : Vec<Fraction> = (0..incoming_queues.len()).map(&weight).collect();
+    // add the size of the next packet of the queue and push the queue with the resulting priority
+    let mut push_next = |queue_id : usize, priority_queue : &mut BinaryHeap<MaxHeapElement>, positions : &[usize]|{
+        let cumulative_size = cumulative_sizes[queue_id] + size(incoming_queues[queue_id][positions[queue_id]]);
+        cumulative_sizes[queue_id] = cumulative_size;
+        let weight = weights[queue_id];
+        priority_queue.push(
+            MaxHeapElement{
+                priority : Fraction::new(cumulative_size.numerator * weight.denominator, cumulative_size.denominator * weight.numerator),
+                edge_id : tie_break_id(queue_id),
+                queue_id,
+            }
+        );
+    };
+    for (queue_id, incoming_queue) in incoming_queues.iter().enumerate(){
+        if !incoming_queue.is_empty(){
+            push_next(queue_id, &mut priority_queue, &positions);
+        }
+    }
+    while let Some(top) = priority_queue.pop(){
+        order.push(top.queue_id);
+        positions[top.queue_id] += 1;
+        if positions[top.queue_id] < incoming_queues[top.queue_id].len(){
+            push_next(top.queue_id, &mut priority_queue, &positions);
+        }
+    }
+    order
+}
+
+// Total size of the packets in the queue
+fn queue_size(incoming_queue : &VecDeque<PacketId>, size : &impl Fn(PacketId) -> Fraction) -> Fraction{
+    incoming_queue.iter().fold(Fraction::new(0, 1), |total, packet_id| total + size(*packet_id))
+}
+
+// Zipper order of the queues, independent of an outgoing edge, e.g. for distributing the capacity of a vertex.
+// Each queue is weighted by the total size of its packets.
+pub fn zipper_order(incoming_queues : &[VecDeque<PacketId>], tie_break_id : impl Fn(usize) -> EdgeId, size : impl Fn(PacketId) -> Fraction) -> Vec<usize>{
+    let weights : Vec<Fraction> = incoming_queues.iter().map(|queue| queue_size(queue, &size)).collect();
+    weighted_zipper(incoming_queues, tie_break_id, size, |queue_id| weights[queue_id])
+}
+
+// The k-th of the n packets of a queue has priority k / n, so all queues are interleaved evenly.
+// With sizes, a packet has the share of its queue's total size up to and including it as priority.
+pub struct Zipper;
+
+impl MergePolicy for Zipper{
+    fn merge_order(&self, context : &MergeContext, incoming_queues : &[VecDeque<PacketId>]) -> Vec<usize>{
+        let order = zipper_order(incoming_queues, |queue_id| context.tie_break_id(queue_id), |packet_id| context.topology.packets[packet_id].size);
+        #[cfg(debug_assertions)]
+        println!("zipper order: {:?}", order);
+        order
+    }
+}
+
+// Packets enter in the order in which they became ready to leave their incoming edge (or were released)
+pub struct GlobalFifo;
+
+impl MergePolicy for GlobalFifo{
+    fn merge_order(&self, context : &MergeContext, incoming_queues : &[VecDeque<PacketId>]) -> Vec<usize>{
+        merge_by_front_key(incoming_queues, |queue_id, packet_id| (context.ready_time(queue_id, packet_id), context.tie_break_id(queue_id)))
+    }
+}
+
+// Among the front packets the one with the smallest commodity id enters first
+pub struct CommodityPriority;
+
+impl MergePolicy for CommodityPriority{
+    fn merge_order(&self, context : &MergeContext, incoming_queues : &[VecDeque<PacketId>]) -> Vec<usize>{
+        merge_by_front_key(incoming_queues, |queue_id, packet_id| (context.topology.packets[packet_id].commodity_id, context.tie_break_id(queue_id)))
+    }
+}
+
+// Among the front packets those of the highest priority class go first, so a packet only waits for packets
+// of a lower class if they are ahead of it in its own queue. Within a class the queues are zipped as by the
+// Zipper, restricted to the packets of this class.
+pub struct ClassPriority;
+
+impl MergePolicy for ClassPriority{
+    fn merge_order(&self, context : &MergeContext, incoming_queues : &[VecDeque<PacketId>]) -> Vec<usize>{
+        let packets = &context.topology.packets;
+        // zipper priority of every packet among the packets of its class in its queue
+        let mut zipper_priorities = HashMap::<PacketId, Fraction>::new();
+        for incoming_queue in incoming_queues{
+            let mut class_sizes = HashMap::<usize, Fraction>::new();
+            for packet_id in incoming_queue{
+                let class_size = class_sizes.entry(packets[*packet_id].priority_class).or_insert(Fraction::new(0, 1));
+                *class_size = *class_size + packets[*packet_id].size;
+            }
+            let mut cumulative_sizes = HashMap::<usize, Fraction>::new();
+            for packet_id in incoming_queue{
+                let class = packets[*packet_id].priority_class;
+                let cumulative_size = cumulative_sizes.entry(class).or_insert(Fraction::new(0, 1));
+                *cumulative_size = *cumulative_size + packets[*packet_id].size;
+                let class_size = class_sizes[&class];
+                zipper_priorities.insert(*packet_id, Fraction::new(cumulative_size.numerator * class_size.denominator, cumulative_size.denominator * class_size.numerator));
+            }
+        }
+        merge_by_front_key(incoming_queues, |queue_id, packet_id| (Reverse(packets[packet_id].priority_class), zipper_priorities[&packet_id], context.tie_break_id(queue_id)))
+    }
+}
+
+// One packet per non-empty queue in turn, in the order of the edge ids and the entering queue last
+pub struct RoundRobin;
+
+impl MergePolicy for RoundRobin{
+    fn merge_order(&self, context : &MergeContext, incoming_queues : &[VecDeque<PacketId>]) -> Vec<usize>{
+        let mut queue_ids : Vec<usize> = (0..incoming_queues.len()).collect();
+        queue_ids.sort_by_key(|queue_id| context.tie_break_id(*queue_id));
+        let mut remaining_lengths : Vec<usize> = incoming_queues.iter().map(|queue| queue.len()).collect();
+        let mut order = Vec::<usize>::new();
+        while remaining_lengths.iter().any(|length| *length > 0){
+            for queue_id in &queue_ids{
+                if remaining_lengths[*queue_id] > 0{
+                    remaining_lengths[*queue_id] -= 1;
+                    order.push(*queue_id);
+                }
+            }
+        }
+        order
+    }
+}
+
+// Zipper where the k-th packet of a queue has priority k / current capacity of the incoming edge,
+// so queues are interleaved in proportion to the capacities. Entering packets and packets leaving
+// an edge whose capacity is currently 0 are weighted by their total size. With sizes, k is replaced
+// by the total size of the packets of the queue up to and including the packet.
+pub struct CapacityWeightedZipper;
+
+impl MergePolicy for CapacityWeightedZipper{
+    fn merge_order(&self, context : &MergeContext, incoming_queues : &[VecDeque<PacketId>]) -> Vec<usize>{
+        let size = |packet_id : PacketId| context.topology.packets[packet_id].size;
+        weighted_zipper(incoming_queues, |queue_id| context.tie_break_id(queue_id), size, |queue_id| {
+            let capacity = context.incoming_edge_ids[queue_id].map(|edge_id| context.topology.edges[edge_id].capacity_at(context.state.time));
+            match capacity{
+                Some(capacity) if capacity.numerator > 0 => capacity,
+                _ => queue_size(&incoming_queues[queue_id], &size),
+            }
+        })
+    }
+}
+
+// Uniformly random interleaving of the queues, reproducible for a given seed
+pub struct SeededRandom{
+    pub seed : u64,
+}
+
+impl MergePolicy for SeededRandom{
+    fn merge_order(&self, context : &MergeContext, incoming_queues : &[VecDeque<PacketId>]) -> Vec<usize>{
+        let mut generator = SplitMix64::from_values(self.seed, &[context.state.time as u64, context.vertex_id as u64, context.outgoing_edge_id as u64]);
+        let mut remaining_lengths : Vec<usize> = incoming_queues.iter().map(|queue| queue.len()).collect();
+        let mut n_remaining : usize = remaining_lengths.iter().sum();
+        let mut order = Vec::<usize>::with_capacity(n_remaining);
+        while n_remaining > 0{
+            // pick a remaining packet uniformly at random, i.e. a queue with probability proportional to its length
+            let mut index = generator.next_below(n_remaining as u64) as usize;
+            let mut queue_id = 0;
+            while index >= remaining_lengths[queue_id]{
+                index -= remaining_lengths[queue_id];
+                queue_id += 1;
+            }
+            remaining_lengths[queue_id] -= 1;
+            n_remaining -= 1;
+            order.push(queue_id);
+        }
+        order
+    }
+}
+
+// Parse a merge policy name as used in network.json and on the command line:
+// zipper, fifo, commodity_priority, class_priority, round_robin, capacity_weighted_zipper or random[:seed]
+pub fn merge_policy_from_name(name : &str) -> Option<Arc<dyn MergePolicy>>{
+    let (name, seed) = match name.split_once(':'){
+        Some((name, seed)) => (name, Some(seed.parse::<u64>().ok()?)),
+        None => (name, None),
+    };
+    if seed.is_some() && name != "random"{
+        return None;
+    }
+    match name{
+        "zipper" => Some(Arc::new(Zipper)),
+        "fifo" => Some(Arc::new(GlobalFifo)),
+        "commodity_priority" => Some(Arc::new(CommodityPriority)),
+        "class_priority" => Some(Arc::new(ClassPriority)),
+        "round_robin" => Some(Arc::new(RoundRobin)),
+        "capacity_weighted_zipper" => Some(Arc::new(CapacityWeightedZipper)),
+        "random" => Some(Arc::new(SeededRandom{seed : seed.unwrap_or(0)})),
+        _ => None,
+    }
+}
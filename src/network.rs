@@ -1,7 +1,15 @@
-use std::collections::{BinaryHeap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
 
-use crate::heap_element::MaxHeapElement;
+use crate::deadlock::{DeadlockReport, detect_deadlock};
+use crate::drop_policy::{DropPolicy, DropDecision, TailDrop};
 use crate::fraction::Fraction;
+use crate::merge_policy::{MergePolicy, MergeContext, Zipper, zipper_order};
+use crate::observer::Observer;
+use crate::service_model::{ServiceModel, CarryOver};
+use crate::release_schedule::ReleaseSchedule;
+use crate::routing_policy::{RoutingPolicy, RoutingContext, FreeFlow, free_flow_distances};
+use crate::schedule::Schedule;
 
 pub type Time = usize;
 pub type VertexId = usize;
@@ -13,6 +21,7 @@ pub type CommodityId = usize;
 pub struct Vertex{
     pub incoming_edges : Vec<EdgeId>,
     pub outgoing_edges : Vec<EdgeId>,
+    pub capacity : Option<Fraction>, // average number of packets passing on to an outgoing edge per time step, unbounded if None
 }
 
 #[derive(Clone)]
@@ -20,9 +29,32 @@ pub struct Edge{
     pub id : EdgeId,
     pub v_from : VertexId,
     pub v_to : VertexId,
-    pub length : usize,
-    pub average_capacity : Fraction,
-    pub current_capacity : Fraction,
+    pub length : Schedule<usize>, // transit time depending on the entrance time
+    pub capacity : Schedule<Fraction>, // average capacity over time
+    pub storage : Option<usize>, // maximum number of packets on the edge, unbounded if None
+    pub inflow_capacity : Option<Fraction>, // average number of packets entering the edge per time step, unbounded if None
+    pub buffer : Option<usize>, // number of packets on the edge beyond which entering packets are lost, see DropPolicy
+}
+
+impl Edge{
+    pub fn capacity_at(&self, time : Time) -> Fraction{
+        self.capacity.at(time)
+    }
+
+    pub fn length_at(&self, entrance_time : Time) -> usize{
+        self.length.at(entrance_time)
+    }
+
+    // Earliest time at which a packet entering at entrance_time can leave the edge;
+    // packets still leave in FIFO order, so they may have to wait for earlier ones
+    pub fn leaving_time(&self, entrance_time : Time) -> Time{
+        entrance_time + self.length_at(entrance_time)
+    }
+
+    // Number of further packets the edge can hold if n_packets packets are on it, None if unbounded
+    pub fn free_storage(&self, n_packets : usize) -> Option<usize>{
+        self.storage.map(|storage| storage.saturating_sub(n_packets))
+    }
 }
 
 #[derive(Clone)]
@@ -30,215 +62,662 @@ pub struct Packet{
     pub id : PacketId,
     pub commodity_id : CommodityId, 
     pub release_time : Time,
-    pub path : Vec<EdgeId>, // edges on path of packet
-    pub entrance_time : Option<Time>,
-    pub path_position : Option<usize>, // index in path : Vec<usize>
+    pub path : Vec<EdgeId>, // edges on path of packet, empty if the packet is routed adaptively
+    pub source : VertexId,
+    pub destination : VertexId,
+    pub size : Fraction, // part of the capacity of an edge the packet takes when leaving it, 1 for ordinary packets
+    pub priority_class : usize, // priority class of the commodity, higher classes go first with the ClassPriority merge policy
 }
 
-// TODO: separate Network into structs Network + State?
-pub struct Network{
+impl Packet{
+    // Whether the packet chooses its edges during the simulation by the RoutingPolicy
+    pub fn is_adaptive(&self) -> bool{
+        self.path.is_empty()
+    }
+}
+
+// Options controlling how a topology is simulated.
+// The initial simulation state depends on them, so reset a network after changing them.
+#[derive(Clone)]
+pub struct SimulationConfig{
+    // Jump over time steps in which no packet can leave an edge or be released
+    pub event_driven : bool,
+    // Order in which packets from different incoming queues enter an edge
+    pub merge_policy : Arc<dyn MergePolicy>,
+    // How many of the packets that completed their transit leave an edge per time step
+    pub service_model : Arc<dyn ServiceModel>,
+    // Which packets are lost at edges with a finite buffer
+    pub drop_policy : Arc<dyn DropPolicy>,
+    // How adaptively routed packets choose their next edge
+    pub routing_policy : Arc<dyn RoutingPolicy>,
+    // Simulate only the time steps before this time, even if packets have not arrived yet
+    pub time_horizon : Option<Time>,
+}
+
+impl Default for SimulationConfig{
+    fn default() -> Self{
+        SimulationConfig{
+            event_driven : false,
+            merge_policy : Arc::new(Zipper),
+            service_model : Arc::new(CarryOver),
+            drop_policy : Arc::new(TailDrop),
+            routing_policy : Arc::new(FreeFlow),
+            time_horizon : None,
+        }
+    }
+}
+
+// Static description of an instance, never modified by a simulation
+#[derive(Clone)]
+pub struct Topology{
     pub vertices : Vec<Vertex>,
     pub edges : Vec<Edge>,
     pub packets : Vec<Packet>,
+    pub release_schedule : ReleaseSchedule, // built from packets, rebuild it with Topology::new when changing them
+    pub config : SimulationConfig,
+}
+
+// Everything a simulation changes; can be created fresh, cloned and rerun against the same topology
+#[derive(Clone)]
+pub struct SimulationState{
     pub edge_queues : Vec<VecDeque<PacketId>>, // i-th queue corresponds to i-th edge
-    pub leaving_queues : Vec<VecDeque<PacketId>>, 
+    pub leaving_queues : Vec<VecDeque<PacketId>>, // packets that completed their transit, they stay here while the next edge is full
+    pub source_queues : Vec<VecDeque<PacketId>>, // released packets waiting for space on the first edge of their path
+    pub current_capacities : Vec<Fraction>, // i-th capacity corresponds to i-th edge, see ServiceModel
+    pub inflow_credits : Vec<Fraction>, // credit of the inflow capacity of the i-th edge, also handled by the ServiceModel
+    pub vertex_credits : Vec<Fraction>, // credit of the capacity of the i-th vertex, also handled by the ServiceModel
+    pub entrance_times : Vec<Option<Time>>, // i-th entrance time corresponds to i-th packet
+    pub path_positions : Vec<Option<usize>>, // index in path : Vec<usize> of the edge the i-th packet is on
+    pub routed_paths : Vec<Vec<EdgeId>>, // edges chosen so far by the i-th packet if it is routed adaptively
     pub time : Time,
     pub packets_arrived : usize,
     pub arrival_times : Vec<Option<Time>>,
+    pub packets_dropped : usize,
+    pub drops : Vec<Option<(Time, EdgeId)>>, // time and edge at which the i-th packet was lost
+}
+
+// A topology together with the state of a simulation on it
+pub struct Network{
+    pub topology : Topology,
+    pub state : SimulationState,
+}
+
+impl Topology{
+    pub fn new(mut vertices : Vec<Vertex>, edges : Vec<Edge>, packets : Vec<Packet>) -> Self{
+        // the outgoing edges of a vertex are searched by id when routing packets
+        for vertex in &mut vertices{
+            vertex.outgoing_edges.sort_unstable();
+        }
+        let release_schedule = ReleaseSchedule::new(&packets);
+        let topology = Topology{vertices, edges, packets, release_schedule, config : SimulationConfig::default()};
+        for packet in topology.packets.iter().filter(|packet| packet.is_adaptive()){
+            assert!(packet.source != packet.destination, "Error: adaptively routed packets should have a destination different from their source");
+            assert!(topology.reachable_from(packet.source)[packet.destination], "Error: the destination of packet {} is not reachable", packet.id);
+        }
+        topology
+    }
+
+    // Whether each vertex can be reached from the given vertex
+    pub fn reachable_from(&self, source : VertexId) -> Vec<bool>{
+        let mut reachable = vec![false; self.vertices.len()];
+        reachable[source] = true;
+        let mut stack = vec![source];
+        while let Some(vertex_id) = stack.pop(){
+            for edge_id in &self.vertices[vertex_id].outgoing_edges{
+                let v_to = self.edges[*edge_id].v_to;
+                if !reachable[v_to]{
+                    reachable[v_to] = true;
+                    stack.push(v_to);
+                }
+            }
+        }
+        reachable
+    }
+}
+
+// Packets of the queues in the given order of queue ids, where each queue id stands for the next packet of its queue
+// Order in which the capacity of the vertex is distributed over the passing queues by the zipper, given by queue ids.
+// Entering packets pass in the order of their release, so the last queue is sorted accordingly.
+pub(crate) fn passing_order(topology : &Topology, vertex_id : VertexId, passing_queues : &mut [VecDeque<PacketId>]) -> Vec<usize>{
+    if let Some(entering_queue) = passing_queues.last_mut(){
+        entering_queue.make_contiguous().sort_by_key(|packet_id| (topology.packets[*packet_id].release_time, *packet_id));
+    }
+    let incoming_edges = &topology.vertices[vertex_id].incoming_edges;
+    zipper_order(passing_queues, |queue_id| incoming_edges.get(queue_id).copied().unwrap_or(EdgeId::MAX), |packet_id| topology.packets[packet_id].size)
+}
+
+pub(crate) fn packets_in_order<'a>(queues : &'a [VecDeque<PacketId>], order : &'a [usize]) -> impl Iterator<Item = PacketId> + 'a{
+    let mut positions = vec![0; queues.len()];
+    order.iter().map(move |queue_id| {
+        positions[*queue_id] += 1;
+        queues[*queue_id][positions[*queue_id] - 1]
+    })
+}
+
+// Edges on the path of the packet, for adaptively routed packets those chosen so far
+fn path_of<'a>(topology : &'a Topology, routed_paths : &'a [Vec<EdgeId>], packet_id : PacketId) -> &'a [EdgeId]{
+    match topology.packets[packet_id].is_adaptive(){
+        true => &routed_paths[packet_id],
+        false => &topology.packets[packet_id].path,
+    }
 }
 
 impl Network{
 
-    pub fn run_simulation(&mut self){
-        while self.packets_arrived < self.packets.len(){
-            #[cfg(debug_assertions)]
-            println!("##### Time: {} #####", self.time);
-            #[cfg(debug_assertions)]
-            println!("### Determining leaving packets ###");
-            self.determine_leaving();
-            #[cfg(debug_assertions)]
-            println!("### Starting node transitions ###");
-            self.node_transitions();
-            #[cfg(debug_assertions)]
-            println!("### Starting packet arrivals ###");
-            self.packet_arrivals();
-            #[cfg(debug_assertions)]
-            println!("#Packets arrived: {}", self.packets_arrived);
-            self.timestep();
+    // Create a network in its initial state
+    pub fn new(topology : Topology) -> Self{
+        let state = SimulationState::new(&topology);
+        Network{topology, state}
+    }
+
+    pub fn run_simulation(&mut self) -> Option<DeadlockReport>{
+        self.state.run_simulation(&self.topology)
+    }
+
+    pub fn step(&mut self, observer : &mut dyn Observer){
+        self.state.step(&self.topology, observer);
+    }
+
+    pub fn run_until(&mut self, time : Time, observer : &mut dyn Observer) -> Option<DeadlockReport>{
+        self.state.run_until(&self.topology, time, observer)
+    }
+
+    pub fn run_until_arrived(&mut self, packet_id : PacketId, observer : &mut dyn Observer) -> Option<DeadlockReport>{
+        self.state.run_until_arrived(&self.topology, packet_id, observer)
+    }
+
+    // Discard the simulation state such that the network can be simulated again
+    pub fn reset(&mut self){
+        self.state = SimulationState::new(&self.topology);
+    }
+}
+
+impl SimulationState{
+
+    // Create the initial state for the topology, i.e. at time 0 with all queues empty
+    pub fn new(topology : &Topology) -> Self{
+        SimulationState{
+            edge_queues : vec![VecDeque::new(); topology.edges.len()],
+            leaving_queues : vec![VecDeque::new(); topology.edges.len()],
+            source_queues : vec![VecDeque::new(); topology.edges.len()],
+            current_capacities : topology.edges.iter().map(|edge| topology.config.service_model.initial_credit(edge.capacity_at(0))).collect(),
+            inflow_credits : topology.edges.iter().map(|edge| topology.config.service_model.initial_credit(edge.inflow_capacity.unwrap_or(Fraction::new(0, 1)))).collect(),
+            vertex_credits : topology.vertices.iter().map(|vertex| topology.config.service_model.initial_credit(vertex.capacity.unwrap_or(Fraction::new(0, 1)))).collect(),
+            entrance_times : vec![None; topology.packets.len()],
+            path_positions : vec![None; topology.packets.len()],
+            routed_paths : vec![Vec::new(); topology.packets.len()],
+            time : 0,
+            packets_arrived : 0,
+            arrival_times : vec![None; topology.packets.len()],
+            packets_dropped : 0,
+            drops : vec![None; topology.packets.len()],
+        }
+    }
+
+    // Simulate until all packets arrived or the time horizon is reached,
+    // stopping early with a report if no packet can ever move again
+    pub fn run_simulation(&mut self, topology : &Topology) -> Option<DeadlockReport>{
+        self.run_until(topology, topology.config.time_horizon.unwrap_or(Time::MAX), &mut ())
+    }
+
+    // Edges on the path of the packet, for adaptively routed packets those chosen so far
+    pub fn path<'a>(&'a self, topology : &'a Topology, packet_id : PacketId) -> &'a [EdgeId]{
+        path_of(topology, &self.routed_paths, packet_id)
+    }
+
+    // Whether all packets have arrived or were dropped
+    pub fn is_finished(&self, topology : &Topology) -> bool{
+        self.packets_arrived + self.packets_dropped == topology.packets.len()
+    }
+
+    // Put the packet on the edge at the given position of its path as if it had entered the edge at entrance_time,
+    // behind the packets already on the edge, e.g. to start a simulation with packets queued on edges
+    pub fn place_packet(&mut self, topology : &Topology, packet_id : PacketId, path_position : usize, entrance_time : Time){
+        assert!(packet_id < topology.packets.len(), "Error: unknown packet id");
+        let packet = &topology.packets[packet_id];
+        assert!(!packet.is_adaptive(), "Error: only packets with a path can be placed on an edge");
+        assert!(path_position < packet.path.len(), "Error: the path of packet {} has no position {}", packet_id, path_position);
+        assert!(self.path_positions[packet_id].is_none() && self.arrival_times[packet_id].is_none() && self.drops[packet_id].is_none(), "Error: packet {} is already in the network", packet_id);
+        // packets enter edges during a time step, so before the current one
+        assert!(packet.release_time <= entrance_time && entrance_time < self.time, "Error: packet {} should enter the edge after its release and before the current time", packet_id);
+        let edge_id = packet.path[path_position];
+        assert!(self.edge_queues[edge_id].back().is_none_or(|last_id| self.entrance_times[*last_id].unwrap() <= entrance_time), "Error: packets should be placed on an edge in the order of their entrance times");
+        self.source_queues.iter_mut().for_each(|source_queue| source_queue.retain(|waiting_id| *waiting_id != packet_id));
+        self.edge_queues[edge_id].push_back(packet_id);
+        self.path_positions[packet_id] = Some(path_position);
+        self.entrance_times[packet_id] = Some(entrance_time);
+    }
+
+    // Simulate the current time step and advance the time if not all packets arrived yet
+    pub fn step(&mut self, topology : &Topology, observer : &mut dyn Observer){
+        self.step_until(topology, Time::MAX, observer);
+    }
+
+    // Simulate the current time step, in event driven mode advancing the time at most to limit
+    fn step_until(&mut self, topology : &Topology, limit : Time, observer : &mut dyn Observer){
+        if self.is_finished(topology){
+            return;
+        }
+        #[cfg(debug_assertions)]
+        println!("##### Time: {} #####", self.time);
+        #[cfg(debug_assertions)]
+        println!("### Determining leaving packets ###");
+        self.determine_leaving(topology);
+        #[cfg(debug_assertions)]
+        println!("### Starting node transitions ###");
+        self.node_transitions(topology, observer);
+        #[cfg(debug_assertions)]
+        println!("### Starting packet arrivals ###");
+        self.packet_arrivals(topology, observer);
+        #[cfg(debug_assertions)]
+        println!("#Packets arrived: {}", self.packets_arrived);
+        observer.step_completed(topology, self);
+        self.timestep(topology, limit);
+    }
+
+    // Simulate all time steps before the given time, i.e. afterwards self.time == time unless all packets arrived earlier
+    // Stops early with a report if no packet can ever move again
+    pub fn run_until(&mut self, topology : &Topology, time : Time, observer : &mut dyn Observer) -> Option<DeadlockReport>{
+        self.run_while(topology, time, observer, |state| !state.is_finished(topology))
+    }
+
+    // Simulate until the given packet has arrived or was dropped or the time horizon is reached,
+    // stopping early with a report if no packet can ever move again
+    pub fn run_until_arrived(&mut self, topology : &Topology, packet_id : PacketId, observer : &mut dyn Observer) -> Option<DeadlockReport>{
+        assert!(packet_id < topology.packets.len(), "Error: unknown packet id");
+        let time_horizon = topology.config.time_horizon.unwrap_or(Time::MAX);
+        self.run_while(topology, time_horizon, observer, |state| state.arrival_times[packet_id].is_none() && state.drops[packet_id].is_none())
+    }
+
+    // Simulate the time steps before the given time while the condition holds, stopping early with a report if no packet
+    // can ever move again. Deadlocks are only searched for in the initial state and after time steps in which no packet moved,
+    // since the search is expensive while packets drain through a bottleneck.
+    fn run_while(&mut self, topology : &Topology, time : Time, observer : &mut dyn Observer, condition : impl Fn(&SimulationState) -> bool) -> Option<DeadlockReport>{
+        let mut moved = false;
+        while condition(self) && self.time < time{
+            if !moved{
+                if let Some(report) = detect_deadlock(topology, self){
+                    return Some(report);
+                }
+            }
+            let mut counter = MovementCounter{observer, n_movements : 0};
+            self.step_until(topology, time, &mut counter);
+            moved = counter.n_movements > 0;
         }
+        None
     }
 
     // Determine the packets leaving the edges
-    fn determine_leaving(&mut self){
+    fn determine_leaving(&mut self, topology : &Topology){
         
-        for edge_id in 0..self.edges.len(){
+        for edge_id in 0..topology.edges.len(){
             #[cfg(debug_assertions)]
             println!("- edge_id: {}", edge_id);
-            // build buffer of candidate leaving packets for edge edge_id
-            let mut buffer_queue = VecDeque::<PacketId>::new();
+            // packets blocked by a full next edge stay in the leaving queue, they were already served
             let edge_queue = &mut self.edge_queues[edge_id];
-            for packet_id in edge_queue.iter_mut(){
-                let entrance_time = self.packets[*packet_id].entrance_time.unwrap();
-                let leaving_time = entrance_time + self.edges[edge_id].length;
+            // sizes of the candidate leaving packets for edge edge_id, i.e. those that completed their transit
+            let mut ready_sizes = Vec::<Fraction>::new();
+            for packet_id in edge_queue.iter(){
+                let leaving_time = topology.edges[edge_id].leaving_time(self.entrance_times[*packet_id].unwrap());
+                // stopping at the first packet still in transit keeps the edge FIFO
                 if leaving_time <= self.time{
-                    buffer_queue.push_back(*packet_id);
+                    ready_sizes.push(topology.packets[*packet_id].size);
                 }
                 else{
                     break;
                 }
             }
             #[cfg(debug_assertions)]
-            println!("buffer_queue length: {}", buffer_queue.len());
+            println!("ready packets: {}", ready_sizes.len());
 
-            let leaving_queue = &mut self.leaving_queues[edge_id];
-            assert!(leaving_queue.is_empty());
-            loop{
-                let n_leaving = Fraction{numerator: (leaving_queue.len() + 1) as i64, denominator : 1};
-                if buffer_queue.is_empty(){
-                    break;
-                }
-                if n_leaving > self.edges[edge_id].current_capacity{
-                    break;
-                }
-                leaving_queue.push_back(*buffer_queue.front().unwrap());
-                buffer_queue.pop_front();
-                edge_queue.pop_front();
-            }
+            let next_capacity = topology.edges[edge_id].capacity_at(self.time + 1);
+            let n_leaving = topology.config.service_model.serve(&mut self.current_capacities[edge_id], &ready_sizes, self.time, next_capacity);
+            assert!(n_leaving <= ready_sizes.len(), "Error: only ready packets can leave");
+            self.leaving_queues[edge_id].extend(edge_queue.drain(..n_leaving));
             #[cfg(debug_assertions)]
-            println!("leaving_queue length: {}", leaving_queue.len());
-            let avg_cap = self.edges[edge_id].average_capacity.clone();
-            let cur_cap = &mut self.edges[edge_id].current_capacity;   
-            if buffer_queue.is_empty(){ // |B_e(t - 1)| <= v^_e(t - 1)
-                *cur_cap = avg_cap;
-            }
-            else{
-                *cur_cap = avg_cap + *cur_cap - (*cur_cap).floor();
-            }
+            println!("leaving_queue length: {}", self.leaving_queues[edge_id].len());
         }
     }
 
     // Determine transition of packets through nodes
-    fn node_transitions(&mut self){
-        for vertex in &self.vertices{
-            // TODO: improve runtime here?
+    fn node_transitions(&mut self, topology : &Topology, observer : &mut dyn Observer){
+        // packets leaving an edge free their space only in the next time step
+        let mut free_storages : Vec<Option<usize>> = topology.edges.iter().map(|edge| edge.free_storage(self.edge_queues[edge.id].len() + self.leaving_queues[edge.id].len())).collect();
+        // free flow distances to the destinations of adaptively routed packets, computed when needed
+        let mut distances = HashMap::<VertexId, Vec<Option<usize>>>::new();
+        for (vertex_id, vertex) in topology.vertices.iter().enumerate(){
+            if vertex.outgoing_edges.is_empty(){
+                continue;
+            }
+            self.route_adaptive_packets(topology, vertex_id, &mut distances);
             for outgoing_edge_id in &vertex.outgoing_edges{
+                let time = self.time;
+                let source_queue = &mut self.source_queues[*outgoing_edge_id];
+                for packet_id in topology.release_schedule.released(self.time, *outgoing_edge_id){
+                    #[cfg(debug_assertions)]
+                    println!("Packet {} enters network, entering_queue", packet_id);
+                    source_queue.push_back(*packet_id);
+                }
+                // packets released now, with fixed or adaptive paths, enter in the order of their ids
+                let n_waiting = source_queue.iter().take_while(|packet_id| topology.packets[**packet_id].release_time < time).count();
+                source_queue.make_contiguous()[n_waiting..].sort_unstable();
+            }
+            let (mut incoming_queues, passing_queues) = self.waiting_queues(topology, vertex_id);
+            let waiting = vertex.capacity.map(|capacity| self.limit_vertex_throughput(topology, vertex_id, capacity, passing_queues, &mut incoming_queues));
+            for (outgoing_position, outgoing_edge_id) in vertex.outgoing_edges.iter().enumerate(){
                 #[cfg(debug_assertions)]
                 println!("- outgoing_edge_id {}", outgoing_edge_id);
-                // initialize priorities and queues of incoming arcs
-                let mut incoming_queues = Vec::<(EdgeId, VecDeque<PacketId>)>::new();
-                for incoming_edge_id in &vertex.incoming_edges{
-                    let mut incoming_queue = VecDeque::<PacketId>::new();
-                    let mut remaining_leaving_queue = VecDeque::<PacketId>::new();
-                    // front-to-back iteration
-                    for packet_id in &self.leaving_queues[*incoming_edge_id]{
-                        let packet = &mut self.packets[*packet_id];
-                        let next_position = packet.path_position.unwrap() + 1;
-                        #[cfg(debug_assertions)]
-                        println!("Packet {} path.len(): {}, path[next]: {}", packet_id, packet.path.len(), packet.path[next_position]);
-                        if packet.path.len() > next_position && packet.path[next_position] == *outgoing_edge_id{
-                            // TODO: Test order!
-                            incoming_queue.push_back(*packet_id);
-                            packet.path_position = Some(next_position);
-                            #[cfg(debug_assertions)]
-                            println!("Packet {} has new path_position {}", packet_id, packet.path_position.unwrap());
-                        }
-                        else{
-                            // TODO: Test order!
-                            remaining_leaving_queue.push_back(*packet_id);
-                        }
-                    }
-                    self.leaving_queues[*incoming_edge_id] = remaining_leaving_queue;
-                    incoming_queues.push((*incoming_edge_id, incoming_queue));
+                let queues = std::mem::take(&mut incoming_queues[outgoing_position]);
+                let n_added = self.merge_into_edge(topology, vertex_id, *outgoing_edge_id, queues, free_storages[*outgoing_edge_id], observer);
+                if let Some(free_storage) = &mut free_storages[*outgoing_edge_id]{
+                    *free_storage -= n_added;
                 }
-                // Add additional queue for packets entering network
-                let mut entering_queue = VecDeque::<PacketId>::new();
-                // TODO: runtime?
-                for (packet_id, packet) in self.packets.iter_mut().enumerate(){
-                    if packet.release_time == self.time && packet.path[0] == *outgoing_edge_id{
-                        #[cfg(debug_assertions)]
-                        println!("Packet {} enters network, entering_queue", packet_id);
-                        entering_queue.push_back(packet_id);
-                        assert_eq!(packet.path_position, None);
-                        #[cfg(debug_assertions)]
-                        println!("Packet {} has new path_position {}", packet_id, 0);
-                        packet.path_position = Some(0);
-                    }
+            }
+            if let (Some(capacity), Some(waiting)) = (vertex.capacity, waiting){
+                self.charge_vertex_credit(topology, vertex_id, capacity, &waiting);
+            }
+            // keep the packets which did not fit into their next edge
+            let path_positions = &self.path_positions;
+            let routed_paths = &self.routed_paths;
+            for incoming_edge_id in &vertex.incoming_edges{
+                self.leaving_queues[*incoming_edge_id].retain(|packet_id| path_of(topology, routed_paths, *packet_id)[path_positions[*packet_id].unwrap()] == *incoming_edge_id);
+            }
+            for outgoing_edge_id in &vertex.outgoing_edges{
+                self.source_queues[*outgoing_edge_id].retain(|packet_id| path_positions[*packet_id].is_none());
+            }
+        }
+    }
+
+    // Packets waiting at the vertex to move on to one of its outgoing edges. incoming_queues[i][j] holds the packets
+    // from the j-th incoming queue moving on to the i-th outgoing edge, the last incoming queue holds the packets
+    // entering the network. passing_queues[j] holds all packets of the j-th incoming queue which want to pass the vertex.
+    pub(crate) fn waiting_queues(&self, topology : &Topology, vertex_id : VertexId) -> (Vec<Vec<VecDeque<PacketId>>>, Vec<VecDeque<PacketId>>){
+        let vertex = &topology.vertices[vertex_id];
+        let n_incoming_queues = vertex.incoming_edges.len() + 1;
+        let mut incoming_queues = vec![vec![VecDeque::<PacketId>::new(); n_incoming_queues]; vertex.outgoing_edges.len()];
+        let mut passing_queues = vec![VecDeque::<PacketId>::new(); n_incoming_queues];
+        for (queue_id, incoming_edge_id) in vertex.incoming_edges.iter().enumerate(){
+            // front-to-back iteration, so every bucket keeps the order of the leaving queue
+            for packet_id in &self.leaving_queues[*incoming_edge_id]{
+                let path = path_of(topology, &self.routed_paths, *packet_id);
+                let next_position = self.path_positions[*packet_id].unwrap() + 1;
+                #[cfg(debug_assertions)]
+                println!("Packet {} path.len(): {}, path[next]: {:?}", packet_id, path.len(), path.get(next_position));
+                // packets at the end of their path arrive instead, packets whose path does not continue here are stuck
+                if let Some(Ok(outgoing_position)) = path.get(next_position).map(|next_edge_id| vertex.outgoing_edges.binary_search(next_edge_id)){
+                    incoming_queues[outgoing_position][queue_id].push_back(*packet_id);
+                    passing_queues[queue_id].push_back(*packet_id);
                 }
-                incoming_queues.push((EdgeId::MAX, entering_queue));
-
-
-                // Build priority_queue for zipper method   
-                let mut priority_queue = BinaryHeap::<MaxHeapElement>::new();
-                let mut original_queue_lengths = Vec::<usize>::new();
-                for (queue_id, (incoming_edge_id, incoming_queue)) in incoming_queues.iter().enumerate(){
-                    original_queue_lengths.push(incoming_queue.len());
-                    if incoming_queue.len() > 0{
-                        priority_queue.push(
-                            MaxHeapElement {
-                                priority : Fraction::new(1, original_queue_lengths[queue_id] as i64),
-                                edge_id : *incoming_edge_id,
-                                queue_id : queue_id,
-                            }
-                        );
-                    }
+            }
+        }
+        // additional queue for packets entering the network, those still waiting from earlier steps first
+        for (outgoing_position, outgoing_edge_id) in vertex.outgoing_edges.iter().enumerate(){
+            let entering_queue = &mut incoming_queues[outgoing_position][n_incoming_queues - 1];
+            entering_queue.extend(self.source_queues[*outgoing_edge_id].iter());
+            passing_queues[n_incoming_queues - 1].extend(entering_queue.iter());
+        }
+        (incoming_queues, passing_queues)
+    }
+
+    // Order in which the merge policy lets the packets of the incoming queues into the outgoing edge, given by queue ids
+    pub(crate) fn merge_order(&self, topology : &Topology, vertex_id : VertexId, outgoing_edge_id : EdgeId, incoming_queues : &[VecDeque<PacketId>]) -> Vec<usize>{
+        let vertex = &topology.vertices[vertex_id];
+        let incoming_edge_ids : Vec<Option<EdgeId>> = vertex.incoming_edges.iter().map(|edge_id| Some(*edge_id)).chain([None]).collect();
+        let order = match incoming_queues.iter().all(|incoming_queue| incoming_queue.is_empty()){
+            true => Vec::new(),
+            false => {
+                let context = MergeContext{
+                    topology,
+                    state : self,
+                    vertex_id,
+                    outgoing_edge_id,
+                    incoming_edge_ids : &incoming_edge_ids,
+                };
+                topology.config.merge_policy.merge_order(&context, incoming_queues)
+            }
+        };
+        assert_eq!(order.len(), incoming_queues.iter().map(|incoming_queue| incoming_queue.len()).sum::<usize>(), "Error: merge order should contain every packet once");
+        order
+    }
+
+    // Let the adaptively routed packets which are released at the vertex or are ready to leave one of its
+    // incoming edges without having chosen their next edge yet choose it. Released packets then wait
+    // in the source queue of their first edge.
+    fn route_adaptive_packets(&mut self, topology : &Topology, vertex_id : VertexId, distances : &mut HashMap<VertexId, Vec<Option<usize>>>){
+        let vertex = &topology.vertices[vertex_id];
+        let mut routing_packets : Vec<PacketId> = vertex.incoming_edges.iter()
+            .flat_map(|edge_id| self.leaving_queues[*edge_id].iter().copied())
+            .filter(|packet_id| {
+                let packet = &topology.packets[*packet_id];
+                packet.is_adaptive() && packet.destination != vertex_id && self.path_positions[*packet_id].unwrap() + 1 == self.routed_paths[*packet_id].len()
+            })
+            .collect();
+        routing_packets.extend(topology.release_schedule.released_at_vertex(self.time, vertex_id));
+        for packet_id in routing_packets{
+            let destination = topology.packets[packet_id].destination;
+            let distances = distances.entry(destination).or_insert_with(|| free_flow_distances(topology, destination, self.time));
+            let context = RoutingContext{
+                topology,
+                state : self,
+                vertex_id,
+                packet_id,
+                distances,
+            };
+            let next_edge_id = topology.config.routing_policy.next_edge(&context).expect("Error: routing policy should choose an edge towards the destination");
+            assert_eq!(topology.edges[next_edge_id].v_from, vertex_id, "Error: routing policy should choose an outgoing edge");
+            #[cfg(debug_assertions)]
+            println!("Packet {} chooses edge {}", packet_id, next_edge_id);
+            self.routed_paths[packet_id].push(next_edge_id);
+            if self.path_positions[packet_id].is_none(){
+                self.source_queues[next_edge_id].push_back(packet_id);
+            }
+        }
+    }
+
+    // Let only as many packets pass the vertex as its capacity allows, distributing the capacity over
+    // the incoming queues by the zipper. The other packets are removed from the incoming_queues,
+    // so they stay in their leaving or source queues. The credit is only charged by charge_vertex_credit
+    // once the next edges admitted the packets, so all packets which want to pass are returned
+    // in the order of the zipper together with their path positions.
+    fn limit_vertex_throughput(&self, topology : &Topology, vertex_id : VertexId, capacity : Fraction, mut passing_queues : Vec<VecDeque<PacketId>>, incoming_queues : &mut [Vec<VecDeque<PacketId>>]) -> Vec<(PacketId, Option<usize>)>{
+        let order = passing_order(topology, vertex_id, &mut passing_queues);
+        let sizes : Vec<Fraction> = packets_in_order(&passing_queues, &order).map(|packet_id| topology.packets[packet_id].size).collect();
+        let mut credit = self.vertex_credits[vertex_id];
+        let n_passing = topology.config.service_model.serve(&mut credit, &sizes, self.time, capacity);
+        let mut n_passing_per_queue = vec![0; passing_queues.len()];
+        for queue_id in order.iter().take(n_passing){
+            n_passing_per_queue[*queue_id] += 1;
+        }
+        #[cfg(debug_assertions)]
+        println!("{} of {} packets pass vertex {}", n_passing, sizes.len(), vertex_id);
+        let passing : HashSet<PacketId> = passing_queues.iter().zip(n_passing_per_queue).flat_map(|(queue, n_passing)| queue.iter().take(n_passing).copied()).collect();
+        for queue in incoming_queues.iter_mut().flatten(){
+            queue.retain(|packet_id| passing.contains(packet_id));
+        }
+        packets_in_order(&passing_queues, &order).map(|packet_id| (packet_id, self.path_positions[packet_id])).collect()
+    }
+
+    // Charge the credit of the vertex for the packets which passed it, given all packets which wanted to pass
+    // and their path positions before. Packets blocked by the storage or inflow capacity of their next edge
+    // did not pass and keep waiting.
+    fn charge_vertex_credit(&mut self, topology : &Topology, vertex_id : VertexId, capacity : Fraction, waiting : &[(PacketId, Option<usize>)]){
+        let (passed, blocked) : (Vec<_>, Vec<_>) = waiting.iter().partition(|(packet_id, path_position)| self.path_positions[*packet_id] != *path_position);
+        let sizes : Vec<Fraction> = passed.iter().chain(&blocked).map(|(packet_id, _)| topology.packets[*packet_id].size).collect();
+        let n_passed = topology.config.service_model.serve_at_most(&mut self.vertex_credits[vertex_id], &sizes, passed.len(), self.time, capacity);
+        assert_eq!(n_passed, passed.len(), "Error: the packets passing vertex {} should fit into its credit", vertex_id);
+    }
+
+    // Move the packets of the incoming queues (the last one for packets entering the network)
+    // into the outgoing edge in the order given by the merge policy, but at most limit many and only as many
+    // as the inflow capacity of the edge admits. Packets may be lost if the edge has a buffer.
+    // Returns by how much the number of packets on the edge increased.
+    fn merge_into_edge(&mut self, topology : &Topology, vertex_id : VertexId, outgoing_edge_id : EdgeId, mut incoming_queues : Vec<VecDeque<PacketId>>, limit : Option<usize>, observer : &mut dyn Observer) -> usize{
+        let vertex = &topology.vertices[vertex_id];
+        let incoming_edge_ids : Vec<Option<EdgeId>> = vertex.incoming_edges.iter().map(|edge_id| Some(*edge_id)).chain([None]).collect();
+        let order = self.merge_order(topology, vertex_id, outgoing_edge_id, &incoming_queues);
+        // a full edge only admits a prefix of the merge order, the other packets are blocked
+        let mut n_merged = limit.map_or(order.len(), |limit| limit.min(order.len()));
+        // the inflow capacity is served like the capacity, packets exceeding it stay in their queues as well;
+        // the blocked packets are offered too, so the service model knows that packets are left waiting
+        if let Some(inflow_capacity) = topology.edges[outgoing_edge_id].inflow_capacity{
+            let sizes : Vec<Fraction> = packets_in_order(&incoming_queues, &order).map(|packet_id| topology.packets[packet_id].size).collect();
+            n_merged = topology.config.service_model.serve_at_most(&mut self.inflow_credits[outgoing_edge_id], &sizes, n_merged, self.time, inflow_capacity);
+        }
+        let n_packets_before = self.edge_queues[outgoing_edge_id].len();
+        for queue_id in order.into_iter().take(n_merged){
+            let packet_id = incoming_queues[queue_id].pop_front().unwrap();
+            #[cfg(debug_assertions)]
+            println!("Packet {} enters edge_queue", packet_id);
+            match incoming_edge_ids[queue_id]{
+                Some(incoming_edge_id) => {
+                    observer.packet_left_edge(packet_id, incoming_edge_id, self.time);
+                    self.path_positions[packet_id] = Some(self.path_positions[packet_id].unwrap() + 1);
                 }
-                #[cfg(debug_assertions)]
-                println!("priority_queue length: {}", priority_queue.len());
-                while !priority_queue.is_empty(){
-                    let top = priority_queue.pop().unwrap();
-                    assert!(top.priority <= Fraction::new(1, 1), "Error: priorities should be at most 1");
-                    let incoming_queue = &mut incoming_queues[top.queue_id].1;
-                    // TODO: test
-                    let packet_id = incoming_queue.pop_front().unwrap();
-                    #[cfg(debug_assertions)]
-                    println!("Packet {} enters edge_queue", packet_id);
-                    // TODO: test
-                    self.edge_queues[*outgoing_edge_id].push_back(packet_id);
-                    
-                    self.packets[packet_id].entrance_time = Some(self.time);
-                
-                    if incoming_queue.len() > 0{
-                        let new_priority = Fraction::new(
-                            (original_queue_lengths[top.queue_id] - incoming_queue.len() + 1) as i64,
-                            original_queue_lengths[top.queue_id] as i64
-                        );
-                        priority_queue.push(
-                            MaxHeapElement{
-                                priority : new_priority,
-                                edge_id : top.edge_id,
-                                queue_id : top.queue_id,
-                            }
-                        );
+                None => self.path_positions[packet_id] = Some(0),
+            }
+            #[cfg(debug_assertions)]
+            println!("Packet {} has new path_position {}", packet_id, self.path_positions[packet_id].unwrap());
+            if let Some(buffer) = topology.edges[outgoing_edge_id].buffer{
+                let n_packets = self.edge_queues[outgoing_edge_id].len() + self.leaving_queues[outgoing_edge_id].len();
+                match topology.config.drop_policy.decide(packet_id, outgoing_edge_id, self.time, n_packets, buffer){
+                    DropDecision::Admit => {}
+                    DropDecision::DropArriving => {
+                        self.drop_packet(packet_id, outgoing_edge_id, observer);
+                        continue;
                     }
-                    
+                    // blocked packets are the oldest ones, without any packets on the edge the entering packet is the oldest one
+                    DropDecision::DropOldest => match self.leaving_queues[outgoing_edge_id].pop_front().or_else(|| self.edge_queues[outgoing_edge_id].pop_front()){
+                        Some(oldest_packet_id) => self.drop_packet(oldest_packet_id, outgoing_edge_id, observer),
+                        None => {
+                            self.drop_packet(packet_id, outgoing_edge_id, observer);
+                            continue;
+                        }
+                    },
                 }
             }
+            self.edge_queues[outgoing_edge_id].push_back(packet_id);
+            self.entrance_times[packet_id] = Some(self.time);
+            observer.packet_merged(packet_id, vertex_id, incoming_edge_ids[queue_id], outgoing_edge_id, self.time);
+            observer.packet_entered_edge(packet_id, outgoing_edge_id, self.time);
         }
+        self.edge_queues[outgoing_edge_id].len().saturating_sub(n_packets_before)
+    }
+
+    // Record that the packet is lost at the edge, it is in no queue anymore
+    fn drop_packet(&mut self, packet_id : PacketId, edge_id : EdgeId, observer : &mut dyn Observer){
+        #[cfg(debug_assertions)]
+        println!("Packet {} is dropped at edge {}", packet_id, edge_id);
+        assert_eq!(self.drops[packet_id], None, "Error: packet should only be dropped once");
+        self.drops[packet_id] = Some((self.time, edge_id));
+        self.packets_dropped += 1;
+        observer.packet_dropped(packet_id, edge_id, self.time);
     }
 
     // Determine packets arrived at the last node of their path
-    fn packet_arrivals(&mut self){
-        for leaving_queue in &self.leaving_queues{
+    fn packet_arrivals(&mut self, topology : &Topology, observer : &mut dyn Observer){
+        for (edge_id, leaving_queue) in self.leaving_queues.iter_mut().enumerate(){
             #[cfg(debug_assertions)]
-            println!("{} packets leaving from leaving_queue", leaving_queue.len());
-            for packet_id in leaving_queue{
-                assert_eq!(self.packets[*packet_id].path_position.unwrap(), self.packets[*packet_id].path.len() - 1, "Error: packet should be at path end");
-                assert_eq!(self.arrival_times[*packet_id], None, "Error: packet should only arrive once");
+            println!("{} packets left in leaving_queue", leaving_queue.len());
+            let path_positions = &self.path_positions;
+            let routed_paths = &self.routed_paths;
+            let arrival_times = &mut self.arrival_times;
+            let packets_arrived = &mut self.packets_arrived;
+            let time = self.time;
+            // the remaining packets are blocked by a full next edge
+            leaving_queue.retain(|packet_id| {
+                // adaptively routed packets may not have chosen their next edge yet
+                if path_positions[*packet_id].unwrap() + 1 < path_of(topology, routed_paths, *packet_id).len() || topology.edges[edge_id].v_to != topology.packets[*packet_id].destination{
+                    return true;
+                }
+                assert_eq!(arrival_times[*packet_id], None, "Error: packet should only arrive once");
                 #[cfg(debug_assertions)]
                 println!("Packet {} has arrived", *packet_id);
-                self.arrival_times[*packet_id] = Some(self.time);
-                self.packets_arrived += 1;
-            }
-        }
-        for leaving_queue in &mut self.leaving_queues {
-            leaving_queue.clear();
+                arrival_times[*packet_id] = Some(time);
+                *packets_arrived += 1;
+                observer.packet_left_edge(*packet_id, edge_id, time);
+                observer.packet_arrived(*packet_id, time);
+                false
+            });
         }
     }
 
     // Advance the time if not all packets arrived yet
-    fn timestep(&mut self){
-        if self.packets_arrived < self.packets.len(){
+    fn timestep(&mut self, topology : &Topology, limit : Time){
+        if !self.is_finished(topology){
             self.time += 1;
+            if topology.config.event_driven{
+                let next_time = self.next_event_time(topology).unwrap_or(self.time).min(limit.max(self.time));
+                if next_time > self.time{
+                    #[cfg(debug_assertions)]
+                    println!("Skipping idle time steps {} to {}", self.time, next_time - 1);
+                    for (edge, current_capacity) in topology.edges.iter().zip(self.current_capacities.iter_mut()){
+                        topology.config.service_model.skip_idle(current_capacity, self.time, next_time, &edge.capacity);
+                    }
+                    for (edge, inflow_credit) in topology.edges.iter().zip(self.inflow_credits.iter_mut()){
+                        if let Some(inflow_capacity) = edge.inflow_capacity{
+                            topology.config.service_model.skip_idle(inflow_credit, self.time, next_time, &Schedule::constant(inflow_capacity));
+                        }
+                    }
+                    for (vertex, vertex_credit) in topology.vertices.iter().zip(self.vertex_credits.iter_mut()){
+                        if let Some(capacity) = vertex.capacity{
+                            topology.config.service_model.skip_idle(vertex_credit, self.time, next_time, &Schedule::constant(capacity));
+                        }
+                    }
+                    self.time = next_time;
+                }
+            }
         }
     }
-}
\ No newline at end of file
+
+    // Earliest time not before self.time at which a packet is released or can leave an edge
+    fn next_event_time(&self, topology : &Topology) -> Option<Time>{
+        // blocked packets try to move on in every time step
+        if self.leaving_queues.iter().chain(self.source_queues.iter()).any(|queue| !queue.is_empty()){
+            return Some(self.time);
+        }
+        let mut next_time = topology.release_schedule.next_release_time(self.time);
+        for (edge_id, edge_queue) in self.edge_queues.iter().enumerate(){
+            // edges are FIFO, so the first packet in the queue is the first to leave
+            if let Some(packet_id) = edge_queue.front(){
+                let leaving_time = topology.edges[edge_id].leaving_time(self.entrance_times[*packet_id].unwrap()).max(self.time);
+                next_time = Some(next_time.map_or(leaving_time, |time| time.min(leaving_time)));
+            }
+        }
+        next_time
+    }
+}
+
+// Forwards the events to the observer and counts the packets entering or leaving an edge or being dropped
+struct MovementCounter<'a>{
+    observer : &'a mut dyn Observer,
+    n_movements : usize,
+}
+
+impl Observer for MovementCounter<'_>{
+    fn packet_entered_edge(&mut self, packet_id : PacketId, edge_id : EdgeId, time : Time){
+        self.n_movements += 1;
+        self.observer.packet_entered_edge(packet_id, edge_id, time);
+    }
+
+    fn packet_left_edge(&mut self, packet_id : PacketId, edge_id : EdgeId, time : Time){
+        self.n_movements += 1;
+        self.observer.packet_left_edge(packet_id, edge_id, time);
+    }
+
+    fn packet_merged(&mut self, packet_id : PacketId, vertex_id : VertexId, incoming_edge_id : Option<EdgeId>, outgoing_edge_id : EdgeId, time : Time){
+        self.observer.packet_merged(packet_id, vertex_id, incoming_edge_id, outgoing_edge_id, time);
+    }
+
+    fn packet_arrived(&mut self, packet_id : PacketId, time : Time){
+        self.observer.packet_arrived(packet_id, time);
+    }
+
+    fn packet_dropped(&mut self, packet_id : PacketId, edge_id : EdgeId, time : Time){
+        self.n_movements += 1;
+        self.observer.packet_dropped(packet_id, edge_id, time);
+    }
+
+    fn step_completed(&mut self, topology : &Topology, state : &SimulationState){
+        self.observer.step_completed(topology, state);
+    }
+}
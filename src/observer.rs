@@ -0,0 +1,100 @@
+use crate::network::{Topology, SimulationState, Time, VertexId, EdgeId, PacketId};
+
+/// Callbacks for instrumenting a simulation, see SimulationState::step.
+/// All methods default to doing nothing, so an observer only implements what it needs.
+/// The unit type () is the observer that ignores everything.
+pub trait Observer{
+    /// The packet entered the queue of the edge
+    fn packet_entered_edge(&mut self, _packet_id : PacketId, _edge_id : EdgeId, _time : Time){}
+
+    /// The packet left the edge, i.e. it moved on to the next edge or arrived
+    fn packet_left_edge(&mut self, _packet_id : PacketId, _edge_id : EdgeId, _time : Time){}
+
+    /// The zipper merged the packet at the vertex from the incoming edge
+    /// (None if the packet enters the network) into the outgoing edge
+    fn packet_merged(&mut self, _packet_id : PacketId, _vertex_id : VertexId, _incoming_edge_id : Option<EdgeId>, _outgoing_edge_id : EdgeId, _time : Time){}
+
+    /// The packet arrived at the last vertex of its path
+    fn packet_arrived(&mut self, _packet_id : PacketId, _time : Time){}
+
+    /// The packet was lost at the edge because of its finite buffer, see DropPolicy
+    fn packet_dropped(&mut self, _packet_id : PacketId, _edge_id : EdgeId, _time : Time){}
+
+    /// All packet movements of the time step state.time are done and the time is about to advance.
+    /// In event driven mode the steps skipped afterwards leave the queues unchanged.
+    fn step_completed(&mut self, _topology : &Topology, _state : &SimulationState){}
+}
+
+impl Observer for (){}
+
+/// An absent observer ignores everything
+impl<O : Observer> Observer for Option<O>{
+    fn packet_entered_edge(&mut self, packet_id : PacketId, edge_id : EdgeId, time : Time){
+        if let Some(observer) = self{
+            observer.packet_entered_edge(packet_id, edge_id, time);
+        }
+    }
+
+    fn packet_left_edge(&mut self, packet_id : PacketId, edge_id : EdgeId, time : Time){
+        if let Some(observer) = self{
+            observer.packet_left_edge(packet_id, edge_id, time);
+        }
+    }
+
+    fn packet_merged(&mut self, packet_id : PacketId, vertex_id : VertexId, incoming_edge_id : Option<EdgeId>, outgoing_edge_id : EdgeId, time : Time){
+        if let Some(observer) = self{
+            observer.packet_merged(packet_id, vertex_id, incoming_edge_id, outgoing_edge_id, time);
+        }
+    }
+
+    fn packet_arrived(&mut self, packet_id : PacketId, time : Time){
+        if let Some(observer) = self{
+            observer.packet_arrived(packet_id, time);
+        }
+    }
+
+    fn packet_dropped(&mut self, packet_id : PacketId, edge_id : EdgeId, time : Time){
+        if let Some(observer) = self{
+            observer.packet_dropped(packet_id, edge_id, time);
+        }
+    }
+
+    fn step_completed(&mut self, topology : &Topology, state : &SimulationState){
+        if let Some(observer) = self{
+            observer.step_completed(topology, state);
+        }
+    }
+}
+
+/// A pair of observers notifies both, the first one first
+impl<A : Observer, B : Observer> Observer for (A, B){
+    fn packet_entered_edge(&mut self, packet_id : PacketId, edge_id : EdgeId, time : Time){
+        self.0.packet_entered_edge(packet_id, edge_id, time);
+        self.1.packet_entered_edge(packet_id, edge_id, time);
+    }
+
+    fn packet_left_edge(&mut self, packet_id : PacketId, edge_id : EdgeId, time : Time){
+        self.0.packet_left_edge(packet_id, edge_id, time);
+        self.1.packet_left_edge(packet_id, edge_id, time);
+    }
+
+    fn packet_merged(&mut self, packet_id : PacketId, vertex_id : VertexId, incoming_edge_id : Option<EdgeId>, outgoing_edge_id : EdgeId, time : Time){
+        self.0.packet_merged(packet_id, vertex_id, incoming_edge_id, outgoing_edge_id, time);
+        self.1.packet_merged(packet_id, vertex_id, incoming_edge_id, outgoing_edge_id, time);
+    }
+
+    fn packet_arrived(&mut self, packet_id : PacketId, time : Time){
+        self.0.packet_arrived(packet_id, time);
+        self.1.packet_arrived(packet_id, time);
+    }
+
+    fn packet_dropped(&mut self, packet_id : PacketId, edge_id : EdgeId, time : Time){
+        self.0.packet_dropped(packet_id, edge_id, time);
+        self.1.packet_dropped(packet_id, edge_id, time);
+    }
+
+    fn step_completed(&mut self, topology : &Topology, state : &SimulationState){
+        self.0.step_completed(topology, state);
+        self.1.step_completed(topology, state);
+    }
+}
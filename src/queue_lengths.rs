@@ -0,0 +1,59 @@
+use crate::network::{Topology, SimulationState, Time, EdgeId};
+use crate::observer::Observer;
+
+// Packets on an edge at the end of a time step
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QueueSample{
+    pub time : Time,
+    // packets which completed their transit but have not left yet, the queue of the fluid model
+    pub n_queued : usize,
+    // all packets on the edge, in transit or queued
+    pub n_on_edge : usize,
+}
+
+// Numbers of queued packets and of all packets on the edge at the current time of the state.
+// As in SimulationState::determine_leaving packets behind a packet still in transit are not queued yet,
+// even if their transit time was shorter.
+pub fn queue_lengths(topology : &Topology, state : &SimulationState, edge_id : EdgeId) -> (usize, usize){
+    let edge = &topology.edges[edge_id];
+    let n_transit_completed = state.edge_queues[edge_id].iter()
+        .take_while(|packet_id| edge.leaving_time(state.entrance_times[**packet_id].unwrap()) <= state.time)
+        .count();
+    let n_leaving = state.leaving_queues[edge_id].len();
+    (n_transit_completed + n_leaving, state.edge_queues[edge_id].len() + n_leaving)
+}
+
+// Observer recording a time series of the queue lengths of every edge at the end of each time step.
+// With only_changes a sample is recorded only if it differs from the previous one of the edge,
+// otherwise the steps skipped in event driven mode are filled in with unchanged samples.
+pub struct QueueLengthRecorder{
+    pub only_changes : bool,
+    pub series : Vec<Vec<QueueSample>>, // i-th series corresponds to i-th edge
+}
+
+impl QueueLengthRecorder{
+    pub fn new(topology : &Topology, only_changes : bool) -> Self{
+        QueueLengthRecorder{only_changes, series : vec![Vec::new(); topology.edges.len()]}
+    }
+}
+
+impl Observer for QueueLengthRecorder{
+    fn step_completed(&mut self, topology : &Topology, state : &SimulationState){
+        for (edge_id, edge_series) in self.series.iter_mut().enumerate(){
+            let (n_queued, n_on_edge) = queue_lengths(topology, state, edge_id);
+            let sample = QueueSample{time : state.time, n_queued, n_on_edge};
+            match edge_series.last().copied(){
+                Some(last) if self.only_changes => {
+                    if (last.n_queued, last.n_on_edge) != (n_queued, n_on_edge){
+                        edge_series.push(sample);
+                    }
+                }
+                Some(last) => {
+                    edge_series.extend((last.time + 1..state.time).map(|time| QueueSample{time, ..last}));
+                    edge_series.push(sample);
+                }
+                None => edge_series.push(sample),
+            }
+        }
+    }
+}
@@ -0,0 +1,50 @@
+// Small deterministic pseudo random number generator (SplitMix64), so that seeded
+// simulations are reproducible without depending on the simulation history
+
+#[derive(Clone, Debug)]
+pub struct SplitMix64{
+    state : u64,
+}
+
+impl SplitMix64{
+    pub fn new(seed : u64) -> Self{
+        SplitMix64{state : seed}
+    }
+
+    // Generator depending only on the seed and the given values, e.g. the time and an edge id
+    pub fn from_values(seed : u64, values : &[u64]) -> Self{
+        let mut generator = SplitMix64::new(seed);
+        for value in values{
+            generator.state ^= *value;
+            generator.state = generator.next_u64();
+        }
+        generator
+    }
+
+    pub fn next_u64(&mut self) -> u64{
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    // Uniformly distributed number in [0, bound)
+    pub fn next_below(&mut self, bound : u64) -> u64{
+        assert!(bound > 0, "Error: bound should be positive");
+        ((self.next_u64() as u128 * bound as u128) >> 64) as u64
+    }
+}
+
+#[test]
+fn test_reproducible(){
+    let mut a = SplitMix64::from_values(7, &[1, 2, 3]);
+    let mut b = SplitMix64::from_values(7, &[1, 2, 3]);
+    let mut c = SplitMix64::from_values(7, &[1, 2, 4]);
+    let a_values : Vec<u64> = (0..10).map(|_| a.next_below(100)).collect();
+    let b_values : Vec<u64> = (0..10).map(|_| b.next_below(100)).collect();
+    let c_values : Vec<u64> = (0..10).map(|_| c.next_below(100)).collect();
+    assert_eq!(a_values, b_values);
+    assert_ne!(a_values, c_values);
+    assert!(a_values.iter().all(|value| *value < 100));
+}
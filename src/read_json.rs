@@ -1,11 +1,19 @@
 use std::fs::File;
 use std::io::Read;
 use std::collections::HashMap;
-use std::collections::VecDeque;
-use serde_json;
 
 use crate::fraction::Fraction;
-use crate::network::{Network, Vertex, Edge, Packet, VertexId, EdgeId, CommodityId};
+use crate::merge_policy::merge_policy_from_name;
+use crate::service_model::service_model_from_name;
+use crate::commodity::{ReleasePattern, split_packets};
+use crate::drop_policy::drop_policy_from_name;
+use crate::equilibrium::{Commodity, simple_paths};
+use crate::routing_policy::routing_policy_from_name;
+use crate::network::{Network, Topology, SimulationConfig, Vertex, Edge, Packet, Time, VertexId, EdgeId, CommodityId};
+use crate::schedule::Schedule;
+
+// Maps the endpoints of an edge to its id
+pub type EdgeToId = HashMap<(VertexId, VertexId), EdgeId>;
 
 pub fn map_vertex_name_id(network_val : &serde_json::Value) -> (HashMap<&str, VertexId>, HashMap<VertexId, String>){
     let mut v_name_to_id : HashMap<&str, VertexId> = HashMap::<&str, VertexId>::new();
@@ -26,64 +34,248 @@ pub fn map_vertex_name_id(network_val : &serde_json::Value) -> (HashMap<&str, Ve
     (v_name_to_id, v_id_to_name)
 }
 
-pub fn get_network(network_val : &serde_json::Value, vertex_name_to_id : &HashMap<&str, VertexId>) -> (Vec::<Edge>, Vec::<Vertex>, HashMap::<(VertexId, VertexId), EdgeId>){
+pub fn get_fraction(fraction_val : &serde_json::Value) -> Fraction{
+    Fraction::new(
+        fraction_val["numerator"].as_i64().unwrap(),
+        fraction_val["denominator"].as_i64().unwrap(),
+    )
+}
+
+// The capacity of an edge is either a fraction or a list of pieces of a piecewise constant capacity,
+// e.g. [{"from": 0, "capacity": {...}}, {"from": 50, "capacity": {"numerator": 0, "denominator": 1}}]
+pub fn get_capacity_schedule(capacity_val : &serde_json::Value) -> Schedule<Fraction>{
+    match capacity_val.as_array(){
+        Some(pieces_val) => Schedule::new(
+            pieces_val.iter().map(|piece_val| (piece_val["from"].as_u64().unwrap() as Time, get_fraction(&piece_val["capacity"]))).collect()
+        ),
+        None => {
+            let capacity = get_fraction(capacity_val);
+            assert!(capacity.numerator > 0, "edge capacities should be positive");
+            Schedule::constant(capacity)
+        }
+    }
+}
+
+// The transit time of an edge is either an integer or a list of pieces of a piecewise constant transit time
+// depending on the entrance time, e.g. [{"from": 0, "transit_time": 2}, {"from": 30, "transit_time": 5}]
+pub fn get_transit_time_schedule(transit_time_val : &serde_json::Value) -> Schedule<usize>{
+    let schedule = match transit_time_val.as_array(){
+        Some(pieces_val) => Schedule::new(
+            pieces_val.iter().map(|piece_val| (piece_val["from"].as_u64().unwrap() as Time, piece_val["transit_time"].as_i64().unwrap() as usize)).collect()
+        ),
+        None => Schedule::constant(transit_time_val.as_i64().unwrap() as usize), // handle rounding errors
+    };
+    assert!(schedule.pieces().iter().all(|(_, length)| *length > 0), "edge lengths should be positive");
+    schedule
+}
+
+pub fn get_network(network_val : &serde_json::Value, vertex_name_to_id : &HashMap<&str, VertexId>) -> (Vec::<Edge>, Vec::<Vertex>, EdgeToId){
     let n_vertices : usize = vertex_name_to_id.len();
     let mut edges : Vec::<Edge> = Vec::<Edge>::new();
-    let mut vertices : Vec<Vertex> = vec![Vertex{incoming_edges : Vec::<VertexId>::new(), outgoing_edges : Vec::<VertexId>::new()}; n_vertices];
-    let mut edge_to_id = std::collections::HashMap::<(VertexId, VertexId), EdgeId>::new();
+    let mut vertices : Vec<Vertex> = vec![Vertex{incoming_edges : Vec::<VertexId>::new(), outgoing_edges : Vec::<VertexId>::new(), capacity : None}; n_vertices];
+    let mut edge_to_id = EdgeToId::new();
     for (edge_id, edge_val) in network_val["edges"].as_array().unwrap().iter().enumerate(){
         let v_from = vertex_name_to_id[edge_val["v_from"].as_str().unwrap()];
         assert!(v_from < n_vertices, "vertex indices should be in [0, n_vertices)");
         let v_to = vertex_name_to_id[edge_val["v_to"].as_str().unwrap()];
         assert!(v_to < n_vertices, "vertex indices should be in [0, n_vertices)");
-        let length = edge_val["transit_time"].as_i64().unwrap() as usize; // handle rounding errors
-        assert!(length > 0, "edge lengths should be positive");
-        let capacity = Fraction{
-            numerator : edge_val["capacity"]["numerator"].as_i64().unwrap(),
-            denominator : edge_val["capacity"]["denominator"].as_i64().unwrap(),
-        };
-        assert!(capacity.numerator > 0, "edge capacities should be positive");
+        let length = get_transit_time_schedule(&edge_val["transit_time"]);
+        let capacity = get_capacity_schedule(&edge_val["capacity"]);
+        // optional maximum number of packets on the edge
+        let storage = edge_val["storage"].as_u64().map(|storage| storage as usize);
+        assert!(storage != Some(0), "edge storages should be positive");
+        // optional maximum average number of packets entering the edge per time step
+        let inflow_capacity = edge_val.get("inflow_capacity").map(get_fraction);
+        if let Some(inflow_capacity) = inflow_capacity{
+            assert!(inflow_capacity.numerator > 0, "edge inflow capacities should be positive");
+        }
+        // optional number of packets on the edge beyond which entering packets are lost
+        let buffer = edge_val["buffer"].as_u64().map(|buffer| buffer as usize);
+        assert!(buffer != Some(0), "edge buffers should be positive");
         edges.push(
             Edge{
                 id : edge_id,
-                v_from : v_from,
-                v_to : v_to,
-                length : length,
-                average_capacity : capacity,
-                current_capacity : capacity,
+                v_from,
+                v_to,
+                length,
+                capacity,
+                storage,
+                inflow_capacity,
+                buffer,
             }
         );
         vertices[v_from].outgoing_edges.push(edge_id);
         vertices[v_to].incoming_edges.push(edge_id);
         edge_to_id.insert((v_from, v_to), edge_id);
     }
+    // optional capacities of vertices by name, e.g. "vertex_capacities": {"v": {"numerator": 1, "denominator": 2}}
+    if let Some(capacities_val) = network_val["vertex_capacities"].as_object(){
+        for (name, capacity_val) in capacities_val{
+            let capacity = get_fraction(capacity_val);
+            assert!(capacity.numerator > 0, "vertex capacities should be positive");
+            vertices[vertex_name_to_id[name.as_str()]].capacity = Some(capacity);
+        }
+    }
     (edges, vertices, edge_to_id)
 }
 
-pub fn get_packets(packets_val : &serde_json::Value, v_name_to_id : &HashMap<&str, VertexId>, edge_to_id : &HashMap::<(VertexId, VertexId), EdgeId>) -> Vec::<Packet>{
+// Read the optional simulation options from network.json,
+// e.g. "merge_policy": "round_robin", "service_model": "token_bucket:3", "drop_policy": "random_early:7"
+// or "time_horizon": 100
+pub fn get_config(network_val : &serde_json::Value) -> SimulationConfig{
+    let mut config = SimulationConfig::default();
+    if let Some(name) = network_val["merge_policy"].as_str(){
+        config.merge_policy = merge_policy_from_name(name).unwrap_or_else(|| panic!("unknown merge policy {}", name));
+    }
+    if let Some(name) = network_val["service_model"].as_str(){
+        config.service_model = service_model_from_name(name).unwrap_or_else(|| panic!("unknown service model {}", name));
+    }
+    if let Some(name) = network_val["drop_policy"].as_str(){
+        config.drop_policy = drop_policy_from_name(name).unwrap_or_else(|| panic!("unknown drop policy {}", name));
+    }
+    if let Some(name) = network_val["routing_policy"].as_str(){
+        config.routing_policy = routing_policy_from_name(name).unwrap_or_else(|| panic!("unknown routing policy {}", name));
+    }
+    config.time_horizon = network_val["time_horizon"].as_u64().map(|time_horizon| time_horizon as Time);
+    config
+}
+
+// The release pattern of a commodity is either a list of release times, e.g. "release_times": [0, 0, 3],
+// or a constant rate, e.g. "release_rate": {"rate": {"numerator": 3, "denominator": 2}, "from": 0, "until": 10}
+pub fn get_release_pattern(commodity_val : &serde_json::Value) -> ReleasePattern{
+    match commodity_val["release_times"].as_array(){
+        Some(times_val) => ReleasePattern::Times(times_val.iter().map(|time_val| time_val.as_u64().unwrap() as Time).collect()),
+        None => {
+            let rate_val = &commodity_val["release_rate"];
+            ReleasePattern::Rate{
+                rate : get_fraction(&rate_val["rate"]),
+                from : rate_val["from"].as_u64().unwrap() as Time,
+                until : rate_val["until"].as_u64().unwrap() as Time,
+            }
+        }
+    }
+}
+
+// The optional size of a packet is either an integer or a fraction, 1 if it is missing
+pub fn get_size(size_val : &serde_json::Value) -> Fraction{
+    let size = match (size_val.is_null(), size_val.as_u64()){
+        (true, _) => Fraction::new(1, 1),
+        (false, Some(size)) => Fraction::new(size as i64, 1),
+        (false, None) => get_fraction(size_val),
+    };
+    assert!(size.numerator > 0, "packet sizes should be positive");
+    size
+}
+
+// Packets are given one by one in "packets" and by the commodities in "commodities" which have "split_ratios",
+// e.g. {"id": 0, "source": "s", "sink": "t", "paths": [["s", "a", "t"], ["s", "b", "t"]],
+// "split_ratios": [{"numerator": 2, "denominator": 3}, {"numerator": 1, "denominator": 3}], "release_times": [0, 0, 1]}.
+// The packets of such commodities follow the packets given one by one, see commodity::split_packets.
+pub fn get_packets(packets_val : &serde_json::Value, v_name_to_id : &HashMap<&str, VertexId>, edge_to_id : &EdgeToId) -> Vec::<Packet>{
     let mut packets = Vec::<Packet>::new();
-    for (packet_id, packet_val) in packets_val["packets"].as_array().unwrap().iter().enumerate() {
+    for (packet_id, packet_val) in packets_val["packets"].as_array().into_iter().flatten().enumerate() {
         let commodity_id = packet_val["commodity_id"].as_u64().unwrap() as CommodityId;
         let release_time = packet_val["release_time"].as_u64().unwrap() as usize;
+        let size = get_size(&packet_val["size"]);
+        // packets without a path are routed adaptively from their source to their destination,
+        // which default to the source and sink of their commodity
+        if packet_val.get("path").is_none(){
+            let commodity_val = packets_val["commodities"].as_array()
+                .and_then(|commodities_val| commodities_val.iter().find(|commodity_val| commodity_val["id"].as_u64() == Some(commodity_id as u64)));
+            let source_val = packet_val.get("source").or_else(|| commodity_val.map(|commodity_val| &commodity_val["source"]));
+            let destination_val = packet_val.get("destination").or_else(|| commodity_val.map(|commodity_val| &commodity_val["sink"]));
+            packets.push(
+                Packet{
+                    id : packet_id,
+                    commodity_id,
+                    release_time,
+                    path : Vec::new(),
+                    source : v_name_to_id[source_val.unwrap().as_str().unwrap()],
+                    destination : v_name_to_id[destination_val.unwrap().as_str().unwrap()],
+                    size,
+                    priority_class : 0,
+                }
+            );
+            continue;
+        }
         let path_length = packet_val["path"].as_array().unwrap().len();
         let vertex_path : Vec<VertexId> = (0..path_length).map(|i| v_name_to_id[packet_val["path"].as_array().unwrap()[i].as_str().unwrap()] as VertexId).collect();
         assert!(vertex_path.len() >= 2, "paths should have length at least 2");
         packets.push(
             Packet{
                 id : packet_id,
-                commodity_id : commodity_id,
-                release_time : release_time,
-                path : vertex_path_to_edge_path(vertex_path, &edge_to_id),
-                entrance_time : None,
-                path_position : None,
+                commodity_id,
+                release_time,
+                source : vertex_path[0],
+                destination : *vertex_path.last().unwrap(),
+                path : vertex_path_to_edge_path(vertex_path, edge_to_id),
+                size,
+                priority_class : 0,
             }
         );
     }
+    for commodity_val in packets_val["commodities"].as_array().into_iter().flatten(){
+        let split_ratios : Vec<Fraction> = match commodity_val["split_ratios"].as_array(){
+            Some(ratios_val) => ratios_val.iter().map(get_fraction).collect(),
+            None => continue,
+        };
+        let source = v_name_to_id[commodity_val["source"].as_str().unwrap()];
+        let sink = v_name_to_id[commodity_val["sink"].as_str().unwrap()];
+        let paths : Vec<Vec<EdgeId>> = commodity_val["paths"].as_array().unwrap().iter().map(|path_val| {
+            let vertex_path : Vec<VertexId> = path_val.as_array().unwrap().iter().map(|name_val| v_name_to_id[name_val.as_str().unwrap()]).collect();
+            assert!(vertex_path.len() >= 2, "paths should have length at least 2");
+            assert!(vertex_path[0] == source && *vertex_path.last().unwrap() == sink, "paths of a commodity should lead from its source to its sink");
+            vertex_path_to_edge_path(vertex_path, edge_to_id)
+        }).collect();
+        let commodity_id = commodity_val["id"].as_u64().unwrap() as CommodityId;
+        let release_pattern = get_release_pattern(commodity_val);
+        let first_packet_id = packets.len();
+        let size = get_size(&commodity_val["size"]);
+        packets.extend(split_packets(commodity_id, source, sink, &release_pattern, &paths, &split_ratios, first_packet_id).into_iter().map(|packet| Packet{size, ..packet}));
+    }
+    // optional priority classes of the commodities, e.g. {"id": 0, "priority_class": 2}, 0 if missing
+    for commodity_val in packets_val["commodities"].as_array().into_iter().flatten(){
+        if let Some(priority_class) = commodity_val["priority_class"].as_u64(){
+            let commodity_id = commodity_val["id"].as_u64().unwrap() as CommodityId;
+            for packet in packets.iter_mut().filter(|packet| packet.commodity_id == commodity_id){
+                packet.priority_class = priority_class as usize;
+            }
+        }
+    }
     packets
 }
 
-fn vertex_path_to_edge_path(vertex_path : Vec<VertexId>, edge_to_id : &std::collections::HashMap::<(VertexId, VertexId), EdgeId>) -> Vec<EdgeId>{
-    assert!(vertex_path.len() > 0);
+// Read the optional commodities of the equilibrium problem from packets.json, e.g.
+// "commodities": [{"id": 0, "source": "s", "sink": "t", "paths": [["s", "a", "t"], ["s", "b", "t"]]}].
+// Without "paths" all simple paths from the source to the sink are candidates.
+// Commodities without a source, e.g. only giving a priority class, are no part of the equilibrium problem.
+pub fn get_commodities(packets_val : &serde_json::Value, topology : &Topology, v_name_to_id : &HashMap<&str, VertexId>, edge_to_id : &EdgeToId) -> Vec<Commodity>{
+    let mut commodities = Vec::<Commodity>::new();
+    for commodity_val in packets_val["commodities"].as_array().into_iter().flatten().filter(|commodity_val| commodity_val.get("source").is_some()){
+        let source = v_name_to_id[commodity_val["source"].as_str().unwrap()];
+        let sink = v_name_to_id[commodity_val["sink"].as_str().unwrap()];
+        let candidate_paths = match commodity_val["paths"].as_array(){
+            Some(paths_val) => paths_val.iter().map(|path_val| {
+                let vertex_path : Vec<VertexId> = path_val.as_array().unwrap().iter().map(|name_val| v_name_to_id[name_val.as_str().unwrap()]).collect();
+                assert!(vertex_path.len() >= 2, "paths should have length at least 2");
+                vertex_path_to_edge_path(vertex_path, edge_to_id)
+            }).collect(),
+            None => simple_paths(topology, source, sink),
+        };
+        commodities.push(
+            Commodity{
+                id : commodity_val["id"].as_u64().unwrap() as CommodityId,
+                source,
+                sink,
+                candidate_paths,
+            }
+        );
+    }
+    commodities
+}
+
+pub fn vertex_path_to_edge_path(vertex_path : Vec<VertexId>, edge_to_id : &EdgeToId) -> Vec<EdgeId>{
+    assert!(!vertex_path.is_empty());
     let mut edge_path = Vec::<EdgeId>::new();
     for id in 0..vertex_path.len()-1{
         edge_path.push(edge_to_id[&(vertex_path[id], vertex_path[id + 1])]);
@@ -93,6 +285,12 @@ fn vertex_path_to_edge_path(vertex_path : Vec<VertexId>, edge_to_id : &std::coll
 }
 
 pub fn read_jsons(instance_directory : &str) -> (Network, HashMap<VertexId, String>){
+    let (network, vertex_id_to_name, _) = read_jsons_with_commodities(instance_directory);
+    (network, vertex_id_to_name)
+}
+
+// Like read_jsons, additionally returning the commodities of the equilibrium problem, see get_commodities
+pub fn read_jsons_with_commodities(instance_directory : &str) -> (Network, HashMap<VertexId, String>, Vec<Commodity>){
     // Define file names
     
     let network_fname = &(instance_directory.to_owned() + "network.json");
@@ -124,25 +322,18 @@ pub fn read_jsons(instance_directory : &str) -> (Network, HashMap<VertexId, Stri
     // Convert to own network class
     let (edges, vertices, edge_to_id) = get_network(&network_val, &vertex_name_to_id);
     let packets = get_packets(&packets_val, &vertex_name_to_id, &edge_to_id);
-    let network = Network{
-        edge_queues : vec![VecDeque::new(); edges.len()],
-        leaving_queues: vec![VecDeque::new(); edges.len()],
-        vertices : vertices,
-        edges : edges,
-        arrival_times : vec![None; packets.len()],  
-        packets : packets,
-        time : 0,
-        packets_arrived : 0,
-    };
-    (network, vertex_id_to_name)
+    let mut topology = Topology::new(vertices, edges, packets);
+    topology.config = get_config(&network_val);
+    let commodities = get_commodities(&packets_val, &topology, &vertex_name_to_id, &edge_to_id);
+    (Network::new(topology), vertex_id_to_name, commodities)
 }
 
 #[test]
 fn test_read_json(){
     let instance_directory = "src/instances/zimmer/";
     let (network, vertex_id_to_name) = read_jsons(instance_directory);
-    assert_eq!(network.vertices.len(), vertex_id_to_name.len()); // One name per vertex
-    assert_eq!(network.vertices.len(), 6);
-    assert_eq!(network.edges.len(), 5);
+    assert_eq!(network.topology.vertices.len(), vertex_id_to_name.len()); // One name per vertex
+    assert_eq!(network.topology.vertices.len(), 6);
+    assert_eq!(network.topology.edges.len(), 5);
     //let mut network = input::input("src/instances/instance_l.txt");
 }
\ No newline at end of file
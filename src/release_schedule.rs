@@ -0,0 +1,48 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::network::{Packet, Time, VertexId, EdgeId, PacketId};
+
+// Index of the packets released at each time, grouped by the first edge of their path
+// or, for adaptively routed packets, by their source. Within a group the packets are ordered by increasing id.
+#[derive(Clone, Default)]
+pub struct ReleaseSchedule{
+    releases : BTreeMap<Time, HashMap<EdgeId, Vec<PacketId>>>,
+    adaptive_releases : BTreeMap<Time, HashMap<VertexId, Vec<PacketId>>>,
+}
+
+impl ReleaseSchedule{
+    pub fn new(packets : &[Packet]) -> Self{
+        let mut releases = BTreeMap::<Time, HashMap<EdgeId, Vec<PacketId>>>::new();
+        let mut adaptive_releases = BTreeMap::<Time, HashMap<VertexId, Vec<PacketId>>>::new();
+        for packet in packets{
+            match packet.is_adaptive(){
+                true => adaptive_releases.entry(packet.release_time).or_default().entry(packet.source).or_default().push(packet.id),
+                false => releases.entry(packet.release_time).or_default().entry(packet.path[0]).or_default().push(packet.id),
+            }
+        }
+        ReleaseSchedule{releases, adaptive_releases}
+    }
+
+    // Packets released at the given time into the given edge
+    pub fn released(&self, time : Time, edge_id : EdgeId) -> &[PacketId]{
+        match self.releases.get(&time).and_then(|edges| edges.get(&edge_id)){
+            Some(packet_ids) => packet_ids,
+            None => &[],
+        }
+    }
+
+    // Adaptively routed packets released at the given time at the given vertex
+    pub fn released_at_vertex(&self, time : Time, vertex_id : VertexId) -> &[PacketId]{
+        match self.adaptive_releases.get(&time).and_then(|vertices| vertices.get(&vertex_id)){
+            Some(packet_ids) => packet_ids,
+            None => &[],
+        }
+    }
+
+    // Earliest time not before the given time at which a packet is released
+    pub fn next_release_time(&self, time : Time) -> Option<Time>{
+        let next_time = self.releases.range(time..).next().map(|(release_time, _)| *release_time);
+        let next_adaptive_time = self.adaptive_releases.range(time..).next().map(|(release_time, _)| *release_time);
+        next_time.into_iter().chain(next_adaptive_time).min()
+    }
+}
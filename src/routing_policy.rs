@@ -0,0 +1,146 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::sync::Arc;
+
+use crate::fraction::Fraction;
+use crate::network::{Topology, SimulationState, Edge, Time, VertexId, EdgeId, PacketId};
+
+// Everything a routing policy may look at when choosing the next edge of an adaptively routed packet
+pub struct RoutingContext<'a>{
+    pub topology : &'a Topology,
+    pub state : &'a SimulationState,
+    pub vertex_id : VertexId,
+    pub packet_id : PacketId,
+    // free flow transit time from every vertex to the destination of the packet
+    // with the transit times of the current time step, None if the destination cannot be reached
+    pub distances : &'a [Option<usize>],
+}
+
+impl RoutingContext<'_>{
+    // Outgoing edges of the vertex from whose end the destination can be reached
+    pub fn candidate_edges(&self) -> impl Iterator<Item = &Edge>{
+        self.topology.vertices[self.vertex_id].outgoing_edges.iter()
+            .map(move |edge_id| &self.topology.edges[*edge_id])
+            .filter(move |edge| self.distances[edge.v_to].is_some())
+    }
+
+    // Free flow transit time to the destination when taking the edge next
+    pub fn free_flow_time(&self, edge : &Edge) -> usize{
+        edge.length_at(self.state.time) + self.distances[edge.v_to].unwrap()
+    }
+}
+
+// Rule by which packets that are only given a source and a destination choose their next edge at each vertex
+pub trait RoutingPolicy : Send + Sync{
+    // Return the outgoing edge the packet takes next, None if the destination cannot be reached
+    fn next_edge(&self, context : &RoutingContext) -> Option<EdgeId>;
+}
+
+// Take the edge on a shortest path with respect to the current transit times, ignoring queues.
+// Ties are broken by the smaller edge id.
+pub struct FreeFlow;
+
+impl RoutingPolicy for FreeFlow{
+    fn next_edge(&self, context : &RoutingContext) -> Option<EdgeId>{
+        context.candidate_edges().min_by_key(|edge| context.free_flow_time(edge)).map(|edge| edge.id)
+    }
+}
+
+// Take the edge minimizing the expected time to the destination, i.e. the waiting time estimated from the total size
+// of the packets on the edge or waiting to enter it and its current capacity, plus the free flow time to the destination.
+// Closed edges are only taken if all candidate edges are closed. Ties are broken by the smaller edge id.
+pub struct QueueAware;
+
+impl RoutingPolicy for QueueAware{
+    fn next_edge(&self, context : &RoutingContext) -> Option<EdgeId>{
+        let state = context.state;
+        let expected_time = |edge : &Edge| {
+            let capacity = edge.capacity_at(state.time);
+            let queued_size = queued_size(context.topology, state, edge);
+            match capacity.numerator > 0{
+                true => Some(Fraction::new(context.free_flow_time(edge) as i64, 1) + Fraction::new(queued_size.numerator * capacity.denominator, queued_size.denominator * capacity.numerator)),
+                false => None,
+            }
+        };
+        let open_edge = context.candidate_edges()
+            .filter_map(|edge| expected_time(edge).map(|time| (time, edge.id)))
+            .min_by_key(|(time, _)| *time)
+            .map(|(_, edge_id)| edge_id);
+        open_edge.or_else(|| FreeFlow.next_edge(context))
+    }
+}
+
+// Instantaneous dynamic equilibrium (IDE): take the first edge of a shortest path to the destination with respect
+// to the current travel times of all edges, i.e. the waiting time in their queues plus their transit time.
+// The travel times change while packets move, so they are recomputed for every decision.
+// Closed edges are only taken if the destination cannot be reached otherwise. Ties are broken by the smaller edge id.
+pub struct InstantaneousDynamicEquilibrium;
+
+impl RoutingPolicy for InstantaneousDynamicEquilibrium{
+    fn next_edge(&self, context : &RoutingContext) -> Option<EdgeId>{
+        let state = context.state;
+        let travel_time = |edge : &Edge| queue_waiting_time(context.topology, state, edge).map(|waiting_time| waiting_time + edge.length_at(state.time));
+        let destination = context.topology.packets[context.packet_id].destination;
+        let distances = shortest_distances(context.topology, destination, travel_time);
+        let open_edge = context.topology.vertices[context.vertex_id].outgoing_edges.iter()
+            .map(|edge_id| &context.topology.edges[*edge_id])
+            .filter_map(|edge| Some((travel_time(edge)? + distances[edge.v_to]?, edge.id)))
+            .min_by_key(|(time, _)| *time)
+            .map(|(_, edge_id)| edge_id);
+        open_edge.or_else(|| FreeFlow.next_edge(context))
+    }
+}
+
+// Total size of the packets on the edge or waiting to enter it
+pub fn queued_size(topology : &Topology, state : &SimulationState, edge : &Edge) -> Fraction{
+    state.edge_queues[edge.id].iter().chain(&state.leaving_queues[edge.id]).chain(&state.source_queues[edge.id])
+        .fold(Fraction::new(0, 1), |total, packet_id| total + topology.packets[*packet_id].size)
+}
+
+// Number of time steps a packet entering the edge now waits until it can leave, given that the packets on the edge
+// or waiting to enter it leave first at the current capacity; None if the edge is closed
+pub fn queue_waiting_time(topology : &Topology, state : &SimulationState, edge : &Edge) -> Option<usize>{
+    let capacity = edge.capacity_at(state.time);
+    if capacity.numerator <= 0{
+        return None;
+    }
+    let queued_size = queued_size(topology, state, edge);
+    Some((queued_size.numerator * capacity.denominator / (queued_size.denominator * capacity.numerator)) as usize)
+}
+
+// Free flow transit times from every vertex to the destination with the transit times at the given time,
+// None for vertices from which the destination cannot be reached
+pub fn free_flow_distances(topology : &Topology, destination : VertexId, time : Time) -> Vec<Option<usize>>{
+    shortest_distances(topology, destination, |edge| Some(edge.length_at(time)))
+}
+
+// Distances from every vertex to the destination with the given edge costs, where edges with cost None are left out.
+// None for vertices from which the destination cannot be reached
+fn shortest_distances(topology : &Topology, destination : VertexId, cost : impl Fn(&Edge) -> Option<usize>) -> Vec<Option<usize>>{
+    let mut distances = vec![None; topology.vertices.len()];
+    let mut heap = BinaryHeap::<Reverse<(usize, VertexId)>>::new();
+    heap.push(Reverse((0, destination)));
+    while let Some(Reverse((distance, vertex_id))) = heap.pop(){
+        if distances[vertex_id].is_some(){
+            continue;
+        }
+        distances[vertex_id] = Some(distance);
+        for edge_id in &topology.vertices[vertex_id].incoming_edges{
+            let edge = &topology.edges[*edge_id];
+            if let (None, Some(edge_cost)) = (distances[edge.v_from], cost(edge)){
+                heap.push(Reverse((distance + edge_cost, edge.v_from)));
+            }
+        }
+    }
+    distances
+}
+
+// Parse a routing policy name as used in network.json and on the command line: free_flow, queue_aware or ide
+pub fn routing_policy_from_name(name : &str) -> Option<Arc<dyn RoutingPolicy>>{
+    match name{
+        "free_flow" => Some(Arc::new(FreeFlow)),
+        "queue_aware" => Some(Arc::new(QueueAware)),
+        "ide" => Some(Arc::new(InstantaneousDynamicEquilibrium)),
+        _ => None,
+    }
+}
@@ -0,0 +1,57 @@
+use crate::network::Time;
+
+// Piecewise constant function of time: the value of a piece holds from its starting time
+// until the starting time of the next piece. The first piece starts at time 0.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Schedule<T>{
+    pieces : Vec<(Time, T)>,
+}
+
+impl<T : Copy> Schedule<T>{
+    // Create a schedule from (starting time, value) pairs with strictly increasing starting times
+    pub fn new(pieces : Vec<(Time, T)>) -> Self{
+        assert!(!pieces.is_empty(), "Error: schedules should have at least one piece");
+        assert_eq!(pieces[0].0, 0, "Error: schedules should start at time 0");
+        assert!(pieces.windows(2).all(|pair| pair[0].0 < pair[1].0), "Error: schedule starting times should be increasing");
+        Schedule{pieces}
+    }
+
+    pub fn constant(value : T) -> Self{
+        Schedule{pieces : vec![(0, value)]}
+    }
+
+    pub fn at(&self, time : Time) -> T{
+        let index = self.pieces.partition_point(|(from, _)| *from <= time);
+        self.pieces[index - 1].1
+    }
+
+    pub fn pieces(&self) -> &[(Time, T)]{
+        &self.pieces
+    }
+
+    // The largest value from the given time on
+    pub fn max_from(&self, time : Time) -> T where T : Ord{
+        let index = self.pieces.partition_point(|(from, _)| *from <= time);
+        self.pieces[index - 1..].iter().map(|(_, value)| *value).max().unwrap()
+    }
+
+    // The value from the last starting time on
+    pub fn last(&self) -> T{
+        self.pieces.last().unwrap().1
+    }
+}
+
+#[test]
+fn test_schedule(){
+    let schedule = Schedule::new(vec![(0, 2), (50, 0), (80, 1)]);
+    assert_eq!(schedule.at(0), 2);
+    assert_eq!(schedule.at(49), 2);
+    assert_eq!(schedule.at(50), 0);
+    assert_eq!(schedule.at(79), 0);
+    assert_eq!(schedule.at(80), 1);
+    assert_eq!(schedule.at(1000), 1);
+    assert_eq!(schedule.last(), 1);
+    assert_eq!(schedule.max_from(10), 2);
+    assert_eq!(schedule.max_from(50), 1);
+    assert_eq!(Schedule::constant(3).at(7), 3);
+}
@@ -0,0 +1,189 @@
+use std::sync::Arc;
+
+use crate::fraction::Fraction;
+use crate::network::Time;
+use crate::schedule::Schedule;
+
+// Rule deciding how many of the packets that completed their transit leave an edge in a time step.
+// The credit of an edge is kept in SimulationState::current_capacities and is the capacity
+// available in the current time step.
+pub trait ServiceModel : Send + Sync{
+    // Credit of an edge before the first time step, given the capacity at time 0
+    fn initial_credit(&self, capacity : Fraction) -> Fraction{
+        capacity
+    }
+
+    // Return how many of the ready packets at the front of the edge queue, given by their sizes, leave at the given time
+    // and update the credit for the next time step, in which the edge has capacity next_capacity.
+    // Every leaving packet takes its size from the credit.
+    fn serve(&self, credit : &mut Fraction, sizes : &[Fraction], time : Time, next_capacity : Fraction) -> usize;
+
+    // Like serve, but at most limit of the packets may leave, the others keep waiting as if they did not fit
+    // into the credit. Models whose credit depends on whether packets are left waiting have to override it.
+    fn serve_at_most(&self, credit : &mut Fraction, sizes : &[Fraction], limit : usize, time : Time, next_capacity : Fraction) -> usize{
+        self.serve(credit, &sizes[..limit.min(sizes.len())], time, next_capacity)
+    }
+
+    // Largest total size served in one time step once the credit is used up, given the maximum capacity from then on,
+    // None if a ready packet of any size leaves eventually while the capacity is positive
+    fn max_service(&self, _max_capacity : Fraction) -> Option<Fraction>{
+        None
+    }
+
+    // Update the credit for the idle time steps from..to in which no packet is ready,
+    // used by the event driven simulation to skip these time steps
+    fn skip_idle(&self, credit : &mut Fraction, from : Time, to : Time, capacity : &Schedule<Fraction>){
+        for time in from..to{
+            let n_leaving = self.serve(credit, &[], time, capacity.at(time + 1));
+            assert_eq!(n_leaving, 0);
+        }
+    }
+}
+
+// Number of packets at the front that fit into the credit together and their total size
+fn n_fitting(credit : Fraction, sizes : &[Fraction]) -> (usize, Fraction){
+    let mut n_leaving = 0;
+    let mut used = Fraction::new(0, 1);
+    while n_leaving < sizes.len() && used + sizes[n_leaving] <= credit{
+        used = used + sizes[n_leaving];
+        n_leaving += 1;
+    }
+    (n_leaving, used)
+}
+
+// Packets leave while they fit into the current capacity. If packets remain, the unused part
+// of the current capacity is carried over to the next time step, otherwise it is lost.
+// For unit sizes the unused part is the fractional part of the current capacity.
+pub struct CarryOver;
+
+impl ServiceModel for CarryOver{
+    fn serve(&self, credit : &mut Fraction, sizes : &[Fraction], time : Time, next_capacity : Fraction) -> usize{
+        self.serve_at_most(credit, sizes, sizes.len(), time, next_capacity)
+    }
+
+    fn serve_at_most(&self, credit : &mut Fraction, sizes : &[Fraction], limit : usize, _time : Time, next_capacity : Fraction) -> usize{
+        let (n_leaving, used) = n_fitting(*credit, &sizes[..limit.min(sizes.len())]);
+        if n_leaving == sizes.len(){ // |B_e(t - 1)| <= v^_e(t - 1)
+            *credit = next_capacity;
+        }
+        else{
+            *credit = next_capacity + *credit - used;
+        }
+        n_leaving
+    }
+
+    fn skip_idle(&self, credit : &mut Fraction, _from : Time, to : Time, capacity : &Schedule<Fraction>){
+        *credit = capacity.at(to);
+    }
+}
+
+// Packets leave while they fit into the capacity of the time step, nothing is carried over,
+// so an edge serves the capacity rounded down per time step for unit sizes (nothing at all if it is below 1)
+// and packets larger than the capacity never leave
+pub struct NoCarryOver;
+
+impl ServiceModel for NoCarryOver{
+    fn serve(&self, credit : &mut Fraction, sizes : &[Fraction], _time : Time, next_capacity : Fraction) -> usize{
+        let (n_leaving, _) = n_fitting(*credit, sizes);
+        *credit = next_capacity;
+        n_leaving
+    }
+
+    fn max_service(&self, max_capacity : Fraction) -> Option<Fraction>{
+        Some(max_capacity)
+    }
+
+    fn skip_idle(&self, credit : &mut Fraction, _from : Time, to : Time, capacity : &Schedule<Fraction>){
+        *credit = capacity.at(to);
+    }
+}
+
+// Unused capacity accumulates as tokens up to the burst size, every leaving packet takes as many tokens as its size,
+// so packets larger than the burst size never leave
+pub struct TokenBucket{
+    pub burst : Fraction,
+}
+
+impl ServiceModel for TokenBucket{
+    fn initial_credit(&self, capacity : Fraction) -> Fraction{
+        capacity.min(self.burst)
+    }
+
+    fn serve(&self, credit : &mut Fraction, sizes : &[Fraction], _time : Time, next_capacity : Fraction) -> usize{
+        let (n_leaving, used) = n_fitting(*credit, sizes);
+        *credit = (*credit - used + next_capacity).min(self.burst);
+        n_leaving
+    }
+
+    fn max_service(&self, _max_capacity : Fraction) -> Option<Fraction>{
+        Some(self.burst)
+    }
+
+    // The idle time steps add the capacity of the time steps from + 1 to to, one piece of the schedule at a time
+    // until the bucket is full
+    fn skip_idle(&self, credit : &mut Fraction, from : Time, to : Time, capacity : &Schedule<Fraction>){
+        let pieces = capacity.pieces();
+        let until = to.saturating_add(1);
+        let mut time = from + 1;
+        while time < until && *credit < self.burst{
+            let index = pieces.partition_point(|(start, _)| *start <= time);
+            let value = pieces[index - 1].1;
+            let end = pieces.get(index).map_or(until, |(start, _)| (*start).min(until));
+            if value.numerator > 0{
+                let missing = self.burst - *credit;
+                let n_steps = (end - time).min(i64::MAX as Time) as i64;
+                // time steps needed to fill the bucket, rounded up
+                let n_to_full = (missing.numerator * value.denominator + missing.denominator * value.numerator - 1) / (missing.denominator * value.numerator);
+                *credit = match n_steps >= n_to_full{
+                    true => self.burst,
+                    false => *credit + Fraction::new(value.numerator * n_steps, value.denominator),
+                };
+            }
+            time = end;
+        }
+    }
+}
+
+// Deterministic schedule: at time t the edge offers floor((t + 1) * c) - floor(t * c) service slots
+// for capacity c, independent of the history. A packet takes as many slots as its size, unused slots are lost.
+// Packets larger than the slots of a time step never leave.
+pub struct Periodic;
+
+impl ServiceModel for Periodic{
+    fn initial_credit(&self, capacity : Fraction) -> Fraction{
+        Fraction::new(capacity.numerator / capacity.denominator, 1)
+    }
+
+    fn serve(&self, credit : &mut Fraction, sizes : &[Fraction], time : Time, next_capacity : Fraction) -> usize{
+        let (n_leaving, _) = n_fitting(*credit, sizes);
+        let slots = |time : i64| (time * next_capacity.numerator) / next_capacity.denominator;
+        *credit = Fraction::new(slots(time as i64 + 2) - slots(time as i64 + 1), 1);
+        n_leaving
+    }
+
+    // a time step offers at most the capacity rounded up as slots
+    fn max_service(&self, max_capacity : Fraction) -> Option<Fraction>{
+        Some(Fraction::new((max_capacity.numerator + max_capacity.denominator - 1) / max_capacity.denominator, 1))
+    }
+
+    fn skip_idle(&self, credit : &mut Fraction, _from : Time, to : Time, capacity : &Schedule<Fraction>){
+        let capacity = capacity.at(to);
+        let slots = |time : i64| (time * capacity.numerator) / capacity.denominator;
+        *credit = Fraction::new(slots(to as i64 + 1) - slots(to as i64), 1);
+    }
+}
+
+// Parse a service model name as used in network.json and on the command line:
+// carry_over, no_carry_over, periodic or token_bucket:<burst>, e.g. token_bucket:3 or token_bucket:5/2
+pub fn service_model_from_name(name : &str) -> Option<Arc<dyn ServiceModel>>{
+    match name.split_once(':'){
+        Some(("token_bucket", burst)) => Some(Arc::new(TokenBucket{burst : burst.parse::<Fraction>().ok()?})),
+        Some(_) => None,
+        None => match name{
+            "carry_over" => Some(Arc::new(CarryOver)),
+            "no_carry_over" => Some(Arc::new(NoCarryOver)),
+            "periodic" => Some(Arc::new(Periodic)),
+            _ => None,
+        },
+    }
+}
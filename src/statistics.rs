@@ -0,0 +1,101 @@
+use std::collections::BTreeMap;
+
+use crate::network::{Topology, SimulationState, Time, EdgeId, PacketId};
+
+// Outcome of the packets of one priority class
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClassStatistics{
+    pub priority_class : usize,
+    pub n_packets : usize,
+    pub n_arrived : usize,
+    pub n_dropped : usize,
+    // over the arrived packets, None if no packet of the class arrived
+    pub mean_travel_time : Option<f64>,
+    pub max_travel_time : Option<Time>,
+}
+
+// Statistics of every priority class occurring among the packets, ordered by class
+pub fn class_statistics(topology : &Topology, state : &SimulationState) -> Vec<ClassStatistics>{
+    let mut travel_times = BTreeMap::<usize, Vec<Time>>::new();
+    let mut statistics = BTreeMap::<usize, ClassStatistics>::new();
+    for packet in &topology.packets{
+        let class_statistics = statistics.entry(packet.priority_class).or_insert(ClassStatistics{
+            priority_class : packet.priority_class,
+            n_packets : 0,
+            n_arrived : 0,
+            n_dropped : 0,
+            mean_travel_time : None,
+            max_travel_time : None,
+        });
+        class_statistics.n_packets += 1;
+        if let Some(arrival_time) = state.arrival_times[packet.id]{
+            class_statistics.n_arrived += 1;
+            travel_times.entry(packet.priority_class).or_default().push(arrival_time - packet.release_time);
+        }
+        if state.drops[packet.id].is_some(){
+            class_statistics.n_dropped += 1;
+        }
+    }
+    for (priority_class, class_travel_times) in travel_times{
+        let class_statistics = statistics.get_mut(&priority_class).unwrap();
+        class_statistics.mean_travel_time = Some(class_travel_times.iter().sum::<Time>() as f64 / class_travel_times.len() as f64);
+        class_statistics.max_travel_time = class_travel_times.iter().max().copied();
+    }
+    statistics.into_values().collect()
+}
+
+// Where the packets are at the current time of a possibly unfinished simulation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Summary{
+    pub time : Time,
+    pub n_packets : usize,
+    pub n_arrived : usize,
+    pub n_dropped : usize,
+    // packets on an edge
+    pub n_in_flight : usize,
+    // released packets waiting to enter their first edge
+    pub n_waiting : usize,
+    // packets to be released at the current time or later
+    pub n_unreleased : usize,
+}
+
+impl Summary{
+    // Packets which have neither arrived nor been dropped
+    pub fn n_pending(&self) -> usize{
+        self.n_in_flight + self.n_waiting + self.n_unreleased
+    }
+}
+
+// Packet on an edge, i.e. it has entered the network but neither arrived nor been dropped
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InFlightPacket{
+    pub packet_id : PacketId,
+    pub edge_id : EdgeId,
+    pub path_position : usize, // index of the edge in the path of the packet
+}
+
+// Packets on an edge ordered by id
+pub fn in_flight_packets(topology : &Topology, state : &SimulationState) -> Vec<InFlightPacket>{
+    (0..topology.packets.len())
+        .filter(|packet_id| state.arrival_times[*packet_id].is_none() && state.drops[*packet_id].is_none())
+        .filter_map(|packet_id| state.path_positions[packet_id].map(|path_position| InFlightPacket{
+            packet_id,
+            edge_id : state.path(topology, packet_id)[path_position],
+            path_position,
+        }))
+        .collect()
+}
+
+pub fn summary(topology : &Topology, state : &SimulationState) -> Summary{
+    let n_in_flight = in_flight_packets(topology, state).len();
+    let n_waiting = state.source_queues.iter().map(|source_queue| source_queue.len()).sum();
+    Summary{
+        time : state.time,
+        n_packets : topology.packets.len(),
+        n_arrived : state.packets_arrived,
+        n_dropped : state.packets_dropped,
+        n_in_flight,
+        n_waiting,
+        n_unreleased : topology.packets.len() - state.packets_arrived - state.packets_dropped - n_in_flight - n_waiting,
+    }
+}
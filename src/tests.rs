@@ -1,3 +1,27 @@
 // This file contains all interface tests but not all unit tests
 mod test_instances;
-mod test_priority_queue;
\ No newline at end of file
+mod test_priority_queue;
+mod test_builder;
+mod test_state;
+mod test_observer;
+mod test_event_driven;
+mod test_release_schedule;
+mod test_node_transitions;
+mod test_merge_policy;
+mod test_service_model;
+mod test_capacity_schedule;
+mod test_transit_time_schedule;
+mod test_storage;
+mod test_deadlock;
+mod test_inflow_capacity;
+mod test_vertex_capacity;
+mod test_drop_policy;
+mod test_adaptive_routing;
+mod test_equilibrium;
+mod test_commodity;
+mod test_packet_size;
+mod test_priority_class;
+mod test_time_horizon;
+mod test_checkpoint;
+mod test_itinerary;
+mod test_queue_lengths;
@@ -0,0 +1,123 @@
+// This file contains tests for packets choosing their edges during the simulation
+#[cfg(test)]
+use {
+    std::sync::Arc,
+    crate::builder::NetworkBuilder,
+    crate::fraction::Fraction,
+    crate::network::{Network, SimulationConfig},
+    crate::read_json,
+    crate::routing_policy::{RoutingPolicy, QueueAware, InstantaneousDynamicEquilibrium, routing_policy_from_name},
+    crate::write_json,
+};
+
+// Direct edge s -> t with transit time 1 and capacity 1 and a detour s -> a -> t with transit time 2 and capacity 10
+#[cfg(test)]
+fn shortcut(routing_policy : Arc<dyn RoutingPolicy>, event_driven : bool) -> Network{
+    let mut builder = NetworkBuilder::new()
+        .edge("s", "t", 1, Fraction::new(1, 1))
+        .edge("s", "a", 1, Fraction::new(10, 1))
+        .edge("a", "t", 1, Fraction::new(10, 1));
+    for _ in 0..4{
+        builder = builder.adaptive_packet(0, 0, "s", "t");
+    }
+    builder.config(SimulationConfig{routing_policy, event_driven, ..Default::default()}).build().0
+}
+
+#[test]
+fn test_free_flow_routing(){
+    for event_driven in [false, true]{
+        let mut network = shortcut(routing_policy_from_name("free_flow").unwrap(), event_driven);
+        assert_eq!(network.run_simulation(), None);
+        assert_eq!(network.state.arrival_times, vec![Some(1), Some(2), Some(3), Some(4)]);
+        assert!((0..4).all(|packet_id| network.state.path(&network.topology, packet_id) == [0]));
+    }
+}
+
+#[test]
+fn test_queue_aware_routing(){
+    for event_driven in [false, true]{
+        // the third packet expects to wait 2 steps at s -> t, which is worse than taking the detour
+        let mut network = shortcut(Arc::new(QueueAware), event_driven);
+        network.run_simulation();
+        assert_eq!(network.state.arrival_times, vec![Some(1), Some(2), Some(2), Some(2)]);
+        assert_eq!(network.state.routed_paths, vec![vec![0], vec![0], vec![1, 2], vec![1, 2]]);
+    }
+}
+
+// Route s -> a -> t with transit time 2 whose last edge is congested by five packets released at a at time 0,
+// and route s -> b -> t with transit time 4. The adaptive packet is released at time 1.
+#[cfg(test)]
+fn congested_route(routing_policy : Arc<dyn RoutingPolicy>) -> Network{
+    let mut builder = NetworkBuilder::new()
+        .edge("s", "a", 1, Fraction::new(1, 1))
+        .edge("a", "t", 1, Fraction::new(1, 1))
+        .edge("s", "b", 2, Fraction::new(1, 1))
+        .edge("b", "t", 2, Fraction::new(1, 1));
+    for _ in 0..5{
+        builder = builder.packet(0, 0, &["a", "t"]);
+    }
+    builder.adaptive_packet(1, 1, "s", "t").config(SimulationConfig{routing_policy, ..Default::default()}).build().0
+}
+
+#[test]
+fn test_ide_routing(){
+    // queue aware routing only sees the empty first edge s -> a and waits at a
+    let mut network = congested_route(Arc::new(QueueAware));
+    network.run_simulation();
+    assert_eq!(network.state.path(&network.topology, 5), [0, 1]);
+    assert_eq!(network.state.arrival_times[5], Some(6));
+    // IDE sees the 4 packets still queued on a -> t, so s -> a -> t takes 6 time steps instead of 4
+    let mut network = congested_route(Arc::new(InstantaneousDynamicEquilibrium));
+    network.run_simulation();
+    assert_eq!(network.state.path(&network.topology, 5), [2, 3]);
+    assert_eq!(network.state.arrival_times[5], Some(5));
+    assert!(routing_policy_from_name("ide").is_some());
+}
+
+#[test]
+fn test_routing_along_the_way(){
+    // the packet decides at a whether to continue directly to t or via b
+    let (mut network, _) = NetworkBuilder::new()
+        .edge("s", "a", 1, Fraction::new(1, 1))
+        .edge("a", "t", 5, Fraction::new(1, 1))
+        .edge("a", "b", 1, Fraction::new(1, 1))
+        .edge("b", "t", 1, Fraction::new(1, 1))
+        .packet(0, 0, &["s", "a", "t"])
+        .adaptive_packet(1, 0, "s", "t")
+        .build();
+    network.run_simulation();
+    assert_eq!(network.state.path(&network.topology, 0), [0, 1]);
+    assert_eq!(network.state.path(&network.topology, 1), [0, 2, 3]);
+    assert_eq!(network.state.arrival_times, vec![Some(6), Some(4)]);
+}
+
+#[test]
+fn test_adaptive_packets_from_json(){
+    let network_val = serde_json::json!({"edges": [
+        {"v_from": "s", "v_to": "a", "transit_time": 1, "capacity": {"numerator": 1, "denominator": 1}},
+        {"v_from": "a", "v_to": "t", "transit_time": 1, "capacity": {"numerator": 1, "denominator": 1}},
+    ]});
+    let packets_val = serde_json::json!({"packets": [
+        {"commodity_id": 0, "release_time": 0, "source": "s", "destination": "t"},
+        {"commodity_id": 0, "release_time": 1, "path": ["a", "t"]},
+    ]});
+    let (vertex_name_to_id, vertex_id_to_name) = read_json::map_vertex_name_id(&network_val);
+    let (edges, vertices, edge_to_id) = read_json::get_network(&network_val, &vertex_name_to_id);
+    let packets = read_json::get_packets(&packets_val, &vertex_name_to_id, &edge_to_id);
+    assert!(packets[0].is_adaptive());
+    assert_eq!((packets[0].source, packets[0].destination), (0, 2));
+    assert_eq!((packets[1].source, packets[1].destination), (1, 2));
+    let mut network = Network::new(crate::network::Topology::new(vertices, edges, packets));
+    network.run_simulation();
+    let output_val = write_json::get_output_val(&network.topology, &network.state, vertex_id_to_name);
+    assert_eq!(output_val["paths"], serde_json::json!([["s", "a", "t"], ["a", "t"]]));
+}
+
+#[test]
+#[should_panic]
+fn test_unreachable_destination(){
+    NetworkBuilder::new()
+        .edge("s", "t", 1, Fraction::new(1, 1))
+        .adaptive_packet(0, 0, "t", "s")
+        .build();
+}
@@ -0,0 +1,44 @@
+// This file contains tests for building networks in memory with NetworkBuilder
+#[cfg(test)]
+use {
+    crate::read_json,
+    crate::builder::NetworkBuilder,
+    crate::fraction::Fraction,
+};
+
+#[test]
+fn test_builder_matches_json(){
+    // same instance as src/tests/instances/i_a1_b1/
+    let (mut built, vertex_id_to_name) = NetworkBuilder::new()
+        .edge("s", "t", 1, Fraction::new(1, 1))
+        .packet(0, 2, &["s", "t"])
+        .packet(0, 2, &["s", "t"])
+        .packet(0, 3, &["s", "t"])
+        .packet(0, 3, &["s", "t"])
+        .build();
+    let (mut read, _) = read_json::read_jsons("src/tests/instances/i_a1_b1/");
+    built.run_simulation();
+    read.run_simulation();
+    assert_eq!(vertex_id_to_name[&0], "s");
+    assert_eq!(vertex_id_to_name[&1], "t");
+    assert_eq!(built.topology.vertices.len(), read.topology.vertices.len());
+    assert_eq!(built.topology.edges.len(), read.topology.edges.len());
+    assert_eq!(built.state.arrival_times, read.state.arrival_times);
+    assert_eq!(built.state.time, read.state.time);
+}
+
+#[test]
+fn test_builder_paths(){
+    let (network, _) = NetworkBuilder::new()
+        .edge("s", "v", 1, Fraction::new(1, 1))
+        .edge("v", "t", 2, Fraction::new(1, 2))
+        .edge("s", "t", 5, Fraction::new(1, 1))
+        .packet(0, 0, &["s", "v", "t"])
+        .packet(1, 0, &["s", "t"])
+        .build();
+    assert_eq!(network.topology.vertices.len(), 3);
+    assert_eq!(network.topology.vertices[0].outgoing_edges, vec![0, 2]);
+    assert_eq!(network.topology.vertices[2].incoming_edges, vec![1, 2]);
+    assert_eq!(network.topology.packets[0].path, vec![0, 1]);
+    assert_eq!(network.topology.packets[1].path, vec![2]);
+}
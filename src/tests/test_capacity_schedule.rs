@@ -0,0 +1,53 @@
+// This file contains tests for time-varying edge capacities
+#[cfg(test)]
+use {
+    crate::builder::NetworkBuilder,
+    crate::fraction::Fraction,
+    crate::network::{Network, Time, SimulationConfig},
+    crate::read_json,
+    crate::schedule::Schedule,
+};
+
+// Single edge with transit time 1 which is closed from time 3 to 6 and has capacity 2 afterwards
+#[cfg(test)]
+fn lane_closure(event_driven : bool, release_times : &[Time]) -> Network{
+    let mut builder = NetworkBuilder::new()
+        .edge("s", "t", 1, Fraction::new(1, 1))
+        .capacity_schedule(vec![(0, Fraction::new(1, 1)), (3, Fraction::new(0, 1)), (6, Fraction::new(2, 1))]);
+    for release_time in release_times{
+        builder = builder.packet(0, *release_time, &["s", "t"]);
+    }
+    builder.config(SimulationConfig{event_driven, ..Default::default()}).build().0
+}
+
+#[test]
+fn test_lane_closure(){
+    for event_driven in [false, true]{
+        let mut network = lane_closure(event_driven, &[0, 0, 0, 0, 0, 0]);
+        network.run_simulation();
+        assert_eq!(network.state.arrival_times, vec![Some(1), Some(2), Some(6), Some(6), Some(7), Some(7)]);
+    }
+}
+
+#[test]
+fn test_closure_while_idle(){
+    // the edge is closed when the second packet becomes ready
+    for event_driven in [false, true]{
+        let mut network = lane_closure(event_driven, &[0, 3, 8]);
+        network.run_simulation();
+        assert_eq!(network.state.arrival_times, vec![Some(1), Some(6), Some(9)]);
+    }
+}
+
+#[test]
+fn test_capacity_schedule_from_json(){
+    let capacity_val = serde_json::json!([
+        {"from": 0, "capacity": {"numerator": 1, "denominator": 2}},
+        {"from": 50, "capacity": {"numerator": 0, "denominator": 1}},
+        {"from": 80, "capacity": {"numerator": 1, "denominator": 1}},
+    ]);
+    let schedule = read_json::get_capacity_schedule(&capacity_val);
+    assert_eq!(schedule, Schedule::new(vec![(0, Fraction::new(1, 2)), (50, Fraction::new(0, 1)), (80, Fraction::new(1, 1))]));
+    let constant = read_json::get_capacity_schedule(&serde_json::json!({"numerator": 3, "denominator": 2}));
+    assert_eq!(constant, Schedule::constant(Fraction::new(3, 2)));
+}
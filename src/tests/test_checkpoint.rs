@@ -0,0 +1,134 @@
+// This file contains tests for saving the simulation state and resuming from it
+#[cfg(test)]
+use {
+    serde_json::json,
+    crate::builder::NetworkBuilder,
+    crate::checkpoint::{get_checkpoint_val, get_state, read_checkpoint, write_checkpoint},
+    crate::fraction::Fraction,
+    crate::network::{Network, SimulationConfig, SimulationState},
+    crate::read_json,
+    crate::routing_policy::routing_policy_from_name,
+};
+
+// Stop the simulation at the given time, resume from its checkpoint and check that the result equals the uninterrupted run
+#[cfg(test)]
+fn check_resume(network : &Network, stop_time : usize){
+    let mut uninterrupted_state = SimulationState::new(&network.topology);
+    uninterrupted_state.run_simulation(&network.topology);
+    let mut interrupted_state = SimulationState::new(&network.topology);
+    interrupted_state.run_until(&network.topology, stop_time, &mut ());
+    let checkpoint_string = serde_json::to_string(&get_checkpoint_val(&interrupted_state)).unwrap();
+    let mut resumed_state = get_state(&network.topology, &serde_json::from_str(&checkpoint_string).unwrap());
+    assert_eq!(get_checkpoint_val(&resumed_state), get_checkpoint_val(&interrupted_state));
+    resumed_state.run_simulation(&network.topology);
+    assert_eq!(get_checkpoint_val(&resumed_state), get_checkpoint_val(&uninterrupted_state));
+    assert_eq!(resumed_state.packets_arrived, uninterrupted_state.packets_arrived);
+}
+
+#[test]
+fn test_resume_instances(){
+    for instance in ["i_a0,5_b1", "i_a1_b1", "y_a0,5_b1", "y_a1_b0,5", "zimmer"]{
+        for event_driven in [false, true]{
+            let (mut network, _) = read_json::read_jsons(&format!("src/tests/instances/{}/", instance));
+            network.topology.config.event_driven = event_driven;
+            for stop_time in [0, 1, 3, 7]{
+                check_resume(&network, stop_time);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_resume_adaptive_routing(){
+    // the direct edge s -> t is congested, so later packets are routed over the detour s -> a -> t
+    let mut builder = NetworkBuilder::new()
+        .edge("s", "t", 1, Fraction::new(1, 2))
+        .edge("s", "a", 1, Fraction::new(3, 2))
+        .edge("a", "t", 2, Fraction::new(3, 2));
+    for release_time in [0, 0, 0, 1, 1, 4]{
+        builder = builder.adaptive_packet(0, release_time, "s", "t");
+    }
+    let (network, _) = builder.config(SimulationConfig{routing_policy : routing_policy_from_name("ide").unwrap(), ..Default::default()}).build();
+    for stop_time in 0..8{
+        check_resume(&network, stop_time);
+    }
+}
+
+#[test]
+fn test_checkpoint_file(){
+    let (mut network, _) = read_json::read_jsons("src/tests/instances/zimmer/");
+    network.run_until(2, &mut ());
+    let checkpoint_fname = std::env::temp_dir().join("routing_test_checkpoint.json");
+    write_checkpoint(&network.state, checkpoint_fname.to_str().unwrap());
+    let resumed_state = read_checkpoint(&network.topology, checkpoint_fname.to_str().unwrap());
+    assert_eq!(get_checkpoint_val(&resumed_state), get_checkpoint_val(&network.state));
+}
+
+// Path s -> a -> t with transit times 2 and capacities 1, two packets are already queued on a -> t
+#[cfg(test)]
+fn queued_instance() -> Network{
+    NetworkBuilder::new()
+        .edge("s", "a", 2, Fraction::new(1, 1))
+        .edge("a", "t", 2, Fraction::new(1, 1))
+        .packet(0, 0, &["s", "a", "t"])
+        .packet(0, 0, &["s", "a", "t"])
+        .packet(0, 3, &["s", "a", "t"])
+        .build().0
+}
+
+#[test]
+fn test_initial_state(){
+    let mut network = queued_instance();
+    network.state.time = 3;
+    network.state.place_packet(&network.topology, 0, 1, 1);
+    network.state.place_packet(&network.topology, 1, 1, 1);
+    let initial_state = network.state.clone();
+    network.run_simulation();
+    // both queued packets completed their transit, but only one can leave per time step
+    assert_eq!(network.state.arrival_times, vec![Some(3), Some(4), Some(7)]);
+    // only the changed entries have to be given
+    let initial_val = json!({
+        "time": 3,
+        "edge_queues": [[], [0, 1]],
+        "entrance_times": [1, 1, null],
+        "path_positions": [1, 1, null],
+    });
+    let mut state = get_state(&network.topology, &initial_val);
+    assert_eq!(get_checkpoint_val(&state), get_checkpoint_val(&initial_state));
+    state.run_simulation(&network.topology);
+    assert_eq!(state.arrival_times, network.state.arrival_times);
+}
+
+#[test]
+#[should_panic]
+fn test_released_packet_outside_network(){
+    // packet 1 was released at time 0 but is neither queued nor finished
+    let network = queued_instance();
+    get_state(&network.topology, &json!({
+        "time": 3,
+        "edge_queues": [[], [0]],
+        "entrance_times": [1, null, null],
+        "path_positions": [1, null, null],
+    }));
+}
+
+#[test]
+#[should_panic]
+fn test_packet_on_wrong_edge(){
+    let network = queued_instance();
+    get_state(&network.topology, &json!({
+        "time": 3,
+        "edge_queues": [[0], [1]],
+        "entrance_times": [1, 1, null],
+        "path_positions": [1, 1, null],
+    }));
+}
+
+#[test]
+#[should_panic]
+fn test_packet_entered_at_current_time(){
+    // packets enter edges during a time step, so a state at time 3 cannot have packet 2 released at time 3 on an edge
+    let mut network = queued_instance();
+    network.state.time = 3;
+    network.state.place_packet(&network.topology, 2, 0, 3);
+}
@@ -0,0 +1,71 @@
+// This file contains tests for packets given by commodities with release patterns and split ratios
+#[cfg(test)]
+use {
+    crate::builder::NetworkBuilder,
+    crate::fraction::Fraction,
+    crate::commodity::{ReleasePattern, split_assignment},
+    crate::read_json,
+};
+
+#[test]
+fn test_split_assignment(){
+    assert_eq!(split_assignment(&[Fraction::new(2, 3), Fraction::new(1, 3)], 6), vec![0, 1, 0, 0, 1, 0]);
+    assert_eq!(split_assignment(&[Fraction::new(1, 2), Fraction::new(1, 2)], 4), vec![0, 1, 0, 1]);
+    assert_eq!(split_assignment(&[Fraction::new(0, 1), Fraction::new(1, 4), Fraction::new(3, 4)], 4), vec![2, 1, 2, 2]);
+    assert!(split_assignment(&[Fraction::new(1, 1)], 0).is_empty());
+}
+
+#[test]
+#[should_panic]
+fn test_split_ratios_sum_up_to_one(){
+    split_assignment(&[Fraction::new(1, 2), Fraction::new(1, 3)], 3);
+}
+
+#[test]
+fn test_release_patterns(){
+    assert_eq!(ReleasePattern::Times(vec![3, 0, 0]).release_times(), vec![0, 0, 3]);
+    // cumulative inflow 3/2 per time step from time 2: 0, 1.5, 3, 4.5, 6
+    assert_eq!(ReleasePattern::Rate{rate : Fraction::new(3, 2), from : 2, until : 6}.release_times(), vec![2, 3, 3, 4, 5, 5]);
+    assert!(ReleasePattern::Rate{rate : Fraction::new(1, 3), from : 0, until : 2}.release_times().is_empty());
+}
+
+#[test]
+fn test_split_commodity(){
+    let (mut network, _) = NetworkBuilder::new()
+        .edge("s", "a", 1, Fraction::new(1, 1))
+        .edge("a", "t", 1, Fraction::new(1, 1))
+        .edge("s", "b", 1, Fraction::new(1, 1))
+        .edge("b", "t", 1, Fraction::new(1, 1))
+        .packet(1, 0, &["s", "a", "t"])
+        .split_commodity(0, ReleasePattern::Rate{rate : Fraction::new(1, 1), from : 0, until : 3}, &[
+            (&["s", "a", "t"], Fraction::new(1, 3)),
+            (&["s", "b", "t"], Fraction::new(2, 3)),
+        ])
+        .build();
+    let paths : Vec<Vec<usize>> = network.topology.packets.iter().map(|packet| packet.path.clone()).collect();
+    assert_eq!(paths, vec![vec![0, 1], vec![2, 3], vec![0, 1], vec![2, 3]]);
+    assert!(network.topology.packets.iter().skip(1).all(|packet| packet.commodity_id == 0));
+    network.run_simulation();
+    assert_eq!(network.state.arrival_times, vec![Some(2), Some(2), Some(3), Some(4)]);
+}
+
+#[test]
+fn test_split_commodity_from_json(){
+    let network_val = serde_json::json!({"edges": [
+        {"v_from": "s", "v_to": "t", "transit_time": 1, "capacity": {"numerator": 1, "denominator": 1}},
+        {"v_from": "s", "v_to": "a", "transit_time": 1, "capacity": {"numerator": 1, "denominator": 1}},
+        {"v_from": "a", "v_to": "t", "transit_time": 1, "capacity": {"numerator": 1, "denominator": 1}},
+    ]});
+    let packets_val = serde_json::json!({"commodities": [
+        {"id": 0, "source": "s", "sink": "t", "paths": [["s", "t"], ["s", "a", "t"]],
+         "split_ratios": [{"numerator": 1, "denominator": 2}, {"numerator": 1, "denominator": 2}], "release_times": [1, 0, 0]},
+        {"id": 1, "source": "s", "sink": "t", "paths": [["s", "t"]],
+         "split_ratios": [{"numerator": 1, "denominator": 1}], "release_rate": {"rate": {"numerator": 1, "denominator": 2}, "from": 0, "until": 4}},
+    ]});
+    let (vertex_name_to_id, _) = read_json::map_vertex_name_id(&network_val);
+    let (_, _, edge_to_id) = read_json::get_network(&network_val, &vertex_name_to_id);
+    let packets = read_json::get_packets(&packets_val, &vertex_name_to_id, &edge_to_id);
+    let summary : Vec<(usize, usize, Vec<usize>)> = packets.iter().map(|packet| (packet.commodity_id, packet.release_time, packet.path.clone())).collect();
+    assert_eq!(summary, vec![(0, 0, vec![0]), (0, 0, vec![1, 2]), (0, 1, vec![0]), (1, 1, vec![0]), (1, 3, vec![0])]);
+    assert!(packets.iter().enumerate().all(|(packet_id, packet)| packet.id == packet_id));
+}
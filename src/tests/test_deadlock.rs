@@ -0,0 +1,216 @@
+// This file contains tests for detecting and reporting deadlocks
+#[cfg(test)]
+use {
+    crate::builder::NetworkBuilder,
+    crate::deadlock::{StuckPacket, StuckReason},
+    crate::fraction::Fraction,
+    crate::network::{Network, SimulationConfig},
+    crate::service_model::service_model_from_name,
+    crate::write_json,
+};
+
+// Two-lane network where s -> a can serve the given number of packets per step and a -> b is closed and full.
+// The packets 1 and 2 leave s -> a after each other, only packet 2 does not need a -> b.
+#[cfg(test)]
+fn blocked_junction(capacity : i64) -> Network{
+    NetworkBuilder::new()
+        .edge("s", "a", 1, Fraction::new(capacity, 1))
+        .edge("a", "b", 1, Fraction::new(1, 1))
+        .capacity_schedule(vec![(0, Fraction::new(0, 1))])
+        .storage(1)
+        .edge("a", "c", 1, Fraction::new(1, 1))
+        .packet(0, 0, &["s", "a", "b"])
+        .packet(0, 0, &["s", "a", "b"])
+        .packet(1, 0, &["s", "a", "c"])
+        .build().0
+}
+
+#[test]
+fn test_gridlock(){
+    for event_driven in [false, true]{
+        // every packet on the ring wants to move on to the next edge, which is full
+        let (mut network, _) = NetworkBuilder::new()
+            .edge("a", "b", 1, Fraction::new(1, 1)).storage(1)
+            .edge("b", "c", 1, Fraction::new(1, 1)).storage(1)
+            .edge("c", "d", 1, Fraction::new(1, 1)).storage(1)
+            .edge("d", "a", 1, Fraction::new(1, 1)).storage(1)
+            .packet(0, 0, &["a", "b", "c"])
+            .packet(0, 0, &["b", "c", "d"])
+            .packet(0, 0, &["c", "d", "a"])
+            .packet(0, 0, &["d", "a", "b"])
+            .config(SimulationConfig{event_driven, ..Default::default()})
+            .build();
+        let report = network.run_simulation().expect("the ring should be deadlocked");
+        // the ring is full at time 1, the deadlock is detected after the following step without movement
+        assert_eq!(report.time, 2);
+        assert_eq!(report.stuck_packets[0], StuckPacket{packet_id : 0, edge_id : Some(0), vertex_id : 1, reason : StuckReason::FullEdge(1)});
+        assert_eq!(report.stuck_packets[3], StuckPacket{packet_id : 3, edge_id : Some(3), vertex_id : 0, reason : StuckReason::FullEdge(0)});
+        assert_eq!(report.edges, vec![0, 1, 2, 3]);
+        assert_eq!(report.vertices, vec![0, 1, 2, 3]);
+        assert_eq!(report.waiting_cycle, vec![0, 1, 2, 3]);
+        assert_eq!(network.state.arrival_times, vec![None; 4]);
+    }
+}
+
+#[test]
+fn test_closed_edge(){
+    let (mut network, _) = NetworkBuilder::new()
+        .edge("s", "t", 1, Fraction::new(1, 1))
+        .capacity_schedule(vec![(0, Fraction::new(1, 1)), (2, Fraction::new(0, 1))])
+        .packet(0, 0, &["s", "t"])
+        .packet(0, 0, &["s", "t"])
+        .packet(0, 0, &["s", "t"])
+        .build();
+    let report = network.run_simulation().expect("the closed edge should stop the simulation");
+    assert_eq!(network.state.arrival_times, vec![Some(1), None, None]);
+    let stuck_ids : Vec<_> = report.stuck_packets.iter().map(|stuck_packet| stuck_packet.packet_id).collect();
+    assert_eq!(stuck_ids, vec![1, 2]);
+    assert!(report.stuck_packets.iter().all(|stuck_packet| stuck_packet.reason == StuckReason::ClosedEdge));
+    assert!(report.waiting_cycle.is_empty());
+}
+
+#[test]
+fn test_closed_edge_with_sizes(){
+    // the credit of 1 left after the small packet never serves the large one once the edge is closed
+    let (mut network, _) = NetworkBuilder::new()
+        .edge("s", "t", 1, Fraction::new(3, 2))
+        .capacity_schedule(vec![(0, Fraction::new(3, 2)), (2, Fraction::new(0, 1))])
+        .packet(0, 0, &["s", "t"])
+        .size(Fraction::new(1, 2))
+        .packet(0, 0, &["s", "t"])
+        .size(Fraction::new(2, 1))
+        .build();
+    let report = network.run_simulation().expect("the large packet should never leave the closed edge");
+    assert_eq!(network.state.arrival_times, vec![Some(1), None]);
+    assert_eq!(report.stuck_packets, vec![StuckPacket{packet_id : 1, edge_id : Some(0), vertex_id : 1, reason : StuckReason::ClosedEdge}]);
+}
+
+#[test]
+fn test_blocked_packet_does_not_block_edge(){
+    // packet 1 waits for a -> b after it was served, so s -> a still serves packet 2, which arrives
+    for (capacity, arrival_time) in [(1, 4), (2, 3)]{
+        let mut network = blocked_junction(capacity);
+        let report = network.run_simulation().expect("packets 0 and 1 should be stuck");
+        let reasons : Vec<_> = report.stuck_packets.iter().map(|stuck_packet| stuck_packet.reason).collect();
+        assert_eq!(reasons, vec![StuckReason::ClosedEdge, StuckReason::FullEdge(1)]);
+        assert_eq!(report.edges, vec![0, 1]);
+        assert!(report.waiting_cycle.is_empty());
+        assert_eq!(network.state.arrival_times[2], Some(arrival_time));
+    }
+}
+
+#[test]
+fn test_packet_too_large(){
+    for (service_model, event_driven) in [("no_carry_over", false), ("no_carry_over", true), ("periodic", false), ("token_bucket:2", false)]{
+        // the truck of size 3 never fits into the capacity 1 per time step, the car behind it waits forever
+        let (mut network, _) = NetworkBuilder::new()
+            .edge("s", "t", 1, Fraction::new(1, 1))
+            .packet(0, 0, &["s", "t"])
+            .size(Fraction::new(3, 1))
+            .packet(0, 0, &["s", "t"])
+            .config(SimulationConfig{service_model : service_model_from_name(service_model).unwrap(), event_driven, ..Default::default()})
+            .build();
+        let report = network.run_simulation().expect("the truck should never leave the edge");
+        let reasons : Vec<_> = report.stuck_packets.iter().map(|stuck_packet| stuck_packet.reason).collect();
+        assert_eq!(reasons, vec![StuckReason::TooLarge, StuckReason::BehindStuckPacket]);
+    }
+    // with carry over the credit grows until the truck fits
+    let (mut network, _) = NetworkBuilder::new()
+        .edge("s", "t", 1, Fraction::new(1, 1))
+        .packet(0, 0, &["s", "t"])
+        .size(Fraction::new(3, 1))
+        .build();
+    assert_eq!(network.run_simulation(), None);
+    assert_eq!(network.state.arrival_times, vec![Some(3)]);
+}
+
+#[test]
+fn test_packet_too_large_for_inflow_capacity(){
+    for (service_model, size) in [("no_carry_over", 1), ("periodic", 2)]{
+        for event_driven in [false, true]{
+            // the inflow capacity 1/2 of a -> t never admits the first packet, the second one waits behind it
+            let (mut network, _) = NetworkBuilder::new()
+                .edge("s", "a", 1, Fraction::new(2, 1))
+                .edge("a", "t", 1, Fraction::new(2, 1))
+                .inflow_capacity(Fraction::new(1, 2))
+                .packet(0, 0, &["s", "a", "t"])
+                .size(Fraction::new(size, 1))
+                .packet(0, 0, &["s", "a", "t"])
+                .config(SimulationConfig{service_model : service_model_from_name(service_model).unwrap(), event_driven, ..Default::default()})
+                .build();
+            let report = network.run_simulation().expect("the first packet should never enter a -> t");
+            let reasons : Vec<_> = report.stuck_packets.iter().map(|stuck_packet| stuck_packet.reason).collect();
+            assert_eq!(reasons, vec![StuckReason::InflowLimit(1), StuckReason::BehindStuckPacket]);
+            assert_eq!(report.edges, vec![0, 1]);
+        }
+    }
+}
+
+#[test]
+fn test_packet_too_large_for_vertex_capacity(){
+    for (service_model, size) in [("no_carry_over", 1), ("periodic", 2)]{
+        for event_driven in [false, true]{
+            // the capacity 1/2 of a never lets the first packet pass, the second one waits behind it
+            let (mut network, _) = NetworkBuilder::new()
+                .edge("s", "a", 1, Fraction::new(2, 1))
+                .edge("a", "t", 1, Fraction::new(2, 1))
+                .vertex_capacity("a", Fraction::new(1, 2))
+                .packet(0, 0, &["s", "a", "t"])
+                .size(Fraction::new(size, 1))
+                .packet(0, 0, &["s", "a", "t"])
+                .config(SimulationConfig{service_model : service_model_from_name(service_model).unwrap(), event_driven, ..Default::default()})
+                .build();
+            let report = network.run_simulation().expect("the first packet should never pass a");
+            let reasons : Vec<_> = report.stuck_packets.iter().map(|stuck_packet| stuck_packet.reason).collect();
+            assert_eq!(reasons, vec![StuckReason::VertexLimit, StuckReason::BehindStuckPacket]);
+            assert_eq!(report.vertices, vec![1]);
+        }
+    }
+}
+
+#[test]
+fn test_inconsistent_path(){
+    let (mut network, _) = NetworkBuilder::new()
+        .edge("s", "a", 1, Fraction::new(1, 1))
+        .edge("a", "t", 1, Fraction::new(1, 1))
+        .edge("b", "t", 1, Fraction::new(1, 1))
+        .packet(0, 0, &["s", "a", "t"])
+        .build();
+    network.topology.packets[0].path = vec![0, 2];
+    let report = network.run_simulation().expect("the packet cannot continue at a");
+    assert_eq!(report.stuck_packets, vec![StuckPacket{packet_id : 0, edge_id : Some(0), vertex_id : 1, reason : StuckReason::InconsistentPath}]);
+    assert!(report.to_string().starts_with("Deadlock at time 2: 1 packets can never move again"));
+}
+
+#[test]
+fn test_deadlock_json(){
+    // the results of a deadlocked simulation are written together with the report
+    let (mut network, vertex_id_to_name) = NetworkBuilder::new()
+        .edge("s", "a", 1, Fraction::new(1, 1))
+        .edge("a", "t", 1, Fraction::new(1, 1))
+        .capacity_schedule(vec![(0, Fraction::new(1, 1)), (3, Fraction::new(0, 1))])
+        .packet(0, 0, &["s", "a", "t"])
+        .packet(0, 0, &["s", "a", "t"])
+        .build();
+    let report = network.run_simulation().expect("the closed edge should stop the simulation");
+    let mut output_val = write_json::get_output_val(&network.topology, &network.state, vertex_id_to_name.clone());
+    output_val["deadlock"] = write_json::get_deadlock_val(&network.topology, &report, &vertex_id_to_name);
+    assert_eq!(output_val["arrival_times"], serde_json::json!([2, null]));
+    assert_eq!(output_val["summary"]["n_in_flight"], 1);
+    assert_eq!(output_val["deadlock"]["stuck_packets"], serde_json::json!([
+        {"packet_id": 1, "edge": {"v_from": "a", "v_to": "t"}, "vertex": "t", "reason": "its edge is closed"},
+    ]));
+    assert_eq!(output_val["deadlock"]["vertices"], serde_json::json!(["t"]));
+}
+
+#[test]
+fn test_no_deadlock_with_spillback(){
+    let (mut network, _) = NetworkBuilder::new()
+        .edge("a", "b", 1, Fraction::new(1, 1)).storage(1)
+        .edge("b", "c", 3, Fraction::new(1, 1)).storage(1)
+        .packet(0, 0, &["a", "b", "c"])
+        .packet(0, 0, &["a", "b", "c"])
+        .build();
+    assert_eq!(network.run_simulation(), None);
+    assert!(network.state.is_finished(&network.topology));
+}
@@ -0,0 +1,126 @@
+// This file contains tests for edges with finite buffers and the policies deciding which packets are lost
+#[cfg(test)]
+use {
+    std::sync::Arc,
+    crate::builder::NetworkBuilder,
+    crate::drop_policy::{DropPolicy, DropDecision, DropOldest, RandomEarlyDrop, drop_policy_from_name},
+    crate::fraction::Fraction,
+    crate::network::{Network, SimulationConfig},
+    crate::write_json,
+};
+
+// Single edge with transit time 3 and capacity 1 which buffers two packets, four packets start at time 0
+#[cfg(test)]
+fn bottleneck(drop_policy : Arc<dyn DropPolicy>) -> Network{
+    NetworkBuilder::new()
+        .edge("s", "t", 3, Fraction::new(1, 1))
+        .buffer(2)
+        .packet(0, 0, &["s", "t"])
+        .packet(0, 0, &["s", "t"])
+        .packet(1, 0, &["s", "t"])
+        .packet(1, 0, &["s", "t"])
+        .config(SimulationConfig{drop_policy, ..Default::default()})
+        .build().0
+}
+
+#[test]
+fn test_tail_drop(){
+    let mut network = bottleneck(drop_policy_from_name("tail_drop").unwrap());
+    assert_eq!(network.run_simulation(), None);
+    assert_eq!(network.state.arrival_times, vec![Some(3), Some(4), None, None]);
+    assert_eq!(network.state.drops, vec![None, None, Some((0, 0)), Some((0, 0))]);
+    assert_eq!(network.state.packets_dropped, 2);
+}
+
+#[test]
+fn test_drop_oldest(){
+    let mut network = bottleneck(Arc::new(DropOldest));
+    network.run_simulation();
+    assert_eq!(network.state.arrival_times, vec![None, None, Some(3), Some(4)]);
+    assert_eq!(network.state.drops, vec![Some((0, 0)), Some((0, 0)), None, None]);
+}
+
+#[test]
+fn test_drop_oldest_blocked(){
+    // packet 4 fills a -> t, which is closed, so packet 0 is blocked on s -> a and is the oldest packet there
+    let (mut network, _) = NetworkBuilder::new()
+        .edge("s", "a", 1, Fraction::new(1, 1))
+        .buffer(2)
+        .edge("a", "t", 1, Fraction::new(1, 1))
+        .capacity_schedule(vec![(0, Fraction::new(0, 1))])
+        .storage(1)
+        .packet(0, 0, &["s", "a", "t"])
+        .packet(0, 1, &["s", "a", "t"])
+        .packet(0, 2, &["s", "a", "t"])
+        .packet(0, 2, &["s", "a", "t"])
+        .packet(1, 0, &["a", "t"])
+        .config(SimulationConfig{drop_policy : Arc::new(DropOldest), ..Default::default()})
+        .build();
+    assert!(network.run_simulation().is_some());
+    // packet 2 replaces the blocked packet 0 and packet 3 then packet 1
+    assert_eq!(network.state.drops, vec![Some((2, 0)), Some((2, 0)), None, None, None]);
+}
+
+#[test]
+fn test_random_early_drop_threshold(){
+    for seed in 0..20{
+        let policy = RandomEarlyDrop{seed};
+        for packet_id in 0..20{
+            // a buffer of 1 admits every packet entering the empty edge
+            assert_eq!(policy.decide(packet_id, 0, 0, 0, 1), DropDecision::Admit);
+            assert_eq!(policy.decide(packet_id, 0, 0, 1, 1), DropDecision::DropArriving);
+            // half of the buffer of 4 is used
+            assert_eq!(policy.decide(packet_id, 0, 0, 2, 4), DropDecision::Admit);
+        }
+    }
+    // beyond half of the buffer some packets are lost
+    let policy = RandomEarlyDrop{seed : 0};
+    let decisions : Vec<_> = (0..20).map(|packet_id| policy.decide(packet_id, 0, 0, 3, 4)).collect();
+    assert!(decisions.contains(&DropDecision::Admit) && decisions.contains(&DropDecision::DropArriving));
+}
+
+#[test]
+fn test_random_early_drop(){
+    let simulate = |seed : u64| {
+        let mut builder = NetworkBuilder::new()
+            .edge("s", "t", 1, Fraction::new(1, 1))
+            .buffer(6);
+        for release_time in 0..20{
+            builder = builder.packet(0, release_time / 2, &["s", "t"]);
+        }
+        let (mut network, _) = builder.config(SimulationConfig{drop_policy : Arc::new(RandomEarlyDrop{seed}), ..Default::default()}).build();
+        network.run_simulation();
+        network.state
+    };
+    let state = simulate(3);
+    assert_eq!(state.drops, simulate(3).drops);
+    assert!(state.packets_dropped > 0);
+    assert_eq!(state.packets_arrived + state.packets_dropped, 20);
+    // while less than half of the buffer is used no packet is lost
+    assert_eq!(state.drops[..3], [None, None, None]);
+}
+
+#[test]
+fn test_dropped_packets_output(){
+    let (mut network, vertex_id_to_name) = NetworkBuilder::new()
+        .edge("s", "t", 1, Fraction::new(1, 1))
+        .buffer(1)
+        .packet(0, 0, &["s", "t"])
+        .packet(0, 0, &["s", "t"])
+        .build();
+    network.run_simulation();
+    let output_val = write_json::get_output_val(&network.topology, &network.state, vertex_id_to_name);
+    assert_eq!(output_val["arrival_times"], serde_json::json!([1, null]));
+    assert_eq!(output_val["travel_times"], serde_json::json!([1, null]));
+    assert_eq!(output_val["dropped_packets"], serde_json::json!([{"packet_id": 1, "time": 0, "v_from": "s", "v_to": "t"}]));
+}
+
+#[test]
+fn test_drop_policy_names(){
+    for name in ["tail_drop", "drop_oldest", "random_early", "random_early:5"]{
+        assert!(drop_policy_from_name(name).is_some());
+    }
+    for name in ["random_early:x", "tail_drop:1", "lifo"]{
+        assert!(drop_policy_from_name(name).is_none());
+    }
+}
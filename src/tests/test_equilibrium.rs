@@ -0,0 +1,106 @@
+// This file contains tests for the search of dynamic equilibria by best responses
+#[cfg(test)]
+use {
+    crate::builder::NetworkBuilder,
+    crate::fraction::Fraction,
+    crate::network::{Network, Topology},
+    crate::equilibrium::{Commodity, solve_equilibrium, simple_paths},
+    crate::read_json,
+};
+
+// Direct edge s -> t with transit time 1 and a detour s -> a -> t with transit time 2, all of capacity 1,
+// and four packets released at time 0 without a path
+#[cfg(test)]
+fn parallel_routes() -> (Network, Vec<Commodity>){
+    let mut builder = NetworkBuilder::new()
+        .edge("s", "t", 1, Fraction::new(1, 1))
+        .edge("s", "a", 1, Fraction::new(1, 1))
+        .edge("a", "t", 1, Fraction::new(1, 1));
+    for _ in 0..4{
+        builder = builder.adaptive_packet(0, 0, "s", "t");
+    }
+    let (network, _) = builder.build();
+    let commodities = vec![Commodity{id : 0, source : 0, sink : 1, candidate_paths : simple_paths(&network.topology, 0, 1)}];
+    (network, commodities)
+}
+
+#[test]
+fn test_simple_paths(){
+    let (network, commodities) = parallel_routes();
+    assert_eq!(commodities[0].candidate_paths, vec![vec![0], vec![1, 2]]);
+    assert!(simple_paths(&network.topology, 1, 0).is_empty());
+}
+
+#[test]
+fn test_best_responses(){
+    let (mut network, commodities) = parallel_routes();
+    let result = solve_equilibrium(&mut network.topology, &commodities, 10);
+    // only the third packet arrives strictly earlier on the detour, after that nobody can improve
+    assert!(result.converged);
+    assert_eq!(result.rounds, 2);
+    assert_eq!(result.path_changes, 1);
+    assert_eq!(result.paths, vec![vec![0], vec![0], vec![1, 2], vec![0]]);
+    assert_eq!(result.arrival_times, vec![Some(1), Some(2), Some(2), Some(3)]);
+    // the paths are stored in the topology
+    network.reset();
+    network.run_simulation();
+    assert_eq!(network.state.arrival_times, result.arrival_times);
+}
+
+#[test]
+fn test_round_limit(){
+    let (mut network, commodities) = parallel_routes();
+    let result = solve_equilibrium(&mut network.topology, &commodities, 0);
+    assert!(!result.converged);
+    assert_eq!(result.rounds, 0);
+    // all packets start on the first candidate path
+    assert_eq!(result.paths, vec![vec![0]; 4]);
+    assert_eq!(result.arrival_times, vec![Some(1), Some(2), Some(3), Some(4)]);
+}
+
+#[test]
+fn test_other_commodities_keep_their_paths(){
+    let (mut network, _) = NetworkBuilder::new()
+        .edge("s", "t", 1, Fraction::new(1, 1))
+        .edge("s", "a", 1, Fraction::new(1, 1))
+        .edge("a", "t", 1, Fraction::new(1, 1))
+        .packet(1, 0, &["s", "t"])
+        .packet(1, 0, &["s", "t"])
+        .packet(0, 0, &["s", "t"])
+        .build();
+    let commodities = vec![Commodity{id : 0, source : 0, sink : 1, candidate_paths : vec![vec![0], vec![1, 2]]}];
+    let result = solve_equilibrium(&mut network.topology, &commodities, 10);
+    assert!(result.converged);
+    assert_eq!(result.paths, vec![vec![0], vec![0], vec![1, 2]]);
+    assert_eq!(result.arrival_times, vec![Some(1), Some(2), Some(2)]);
+}
+
+#[test]
+fn test_commodities_from_json(){
+    let network_val = serde_json::json!({"edges": [
+        {"v_from": "s", "v_to": "t", "transit_time": 1, "capacity": {"numerator": 1, "denominator": 1}},
+        {"v_from": "s", "v_to": "a", "transit_time": 1, "capacity": {"numerator": 1, "denominator": 1}},
+        {"v_from": "a", "v_to": "t", "transit_time": 1, "capacity": {"numerator": 1, "denominator": 1}},
+    ]});
+    let packets_val = serde_json::json!({
+        "commodities": [
+            {"id": 0, "source": "s", "sink": "t"},
+            {"id": 1, "source": "s", "sink": "t", "paths": [["s", "a", "t"]]},
+        ],
+        "packets": [
+            {"commodity_id": 0, "release_time": 0},
+            {"commodity_id": 1, "release_time": 0},
+        ],
+    });
+    let (vertex_name_to_id, _) = read_json::map_vertex_name_id(&network_val);
+    let (edges, vertices, edge_to_id) = read_json::get_network(&network_val, &vertex_name_to_id);
+    let packets = read_json::get_packets(&packets_val, &vertex_name_to_id, &edge_to_id);
+    // packets without path, source and destination travel between the source and sink of their commodity
+    assert!(packets.iter().all(|packet| packet.is_adaptive() && packet.source == 0 && packet.destination == 1));
+    let topology = Topology::new(vertices, edges, packets);
+    let commodities = read_json::get_commodities(&packets_val, &topology, &vertex_name_to_id, &edge_to_id);
+    assert_eq!(commodities, vec![
+        Commodity{id : 0, source : 0, sink : 1, candidate_paths : vec![vec![0], vec![1, 2]]},
+        Commodity{id : 1, source : 0, sink : 1, candidate_paths : vec![vec![1, 2]]},
+    ]);
+}
@@ -0,0 +1,76 @@
+// This file contains tests comparing the event driven simulation with the time stepping one
+#[cfg(test)]
+use {
+    crate::read_json,
+    crate::builder::NetworkBuilder,
+    crate::fraction::Fraction,
+    crate::network::{Network, SimulationConfig},
+};
+
+#[cfg(test)]
+fn count_steps(network : &mut Network) -> usize{
+    let mut n_steps = 0;
+    while !network.state.is_finished(&network.topology){
+        network.step(&mut ());
+        n_steps += 1;
+    }
+    n_steps
+}
+
+#[cfg(test)]
+fn assert_identical_results(instance_directory : &str){
+    let (mut stepped, _) = read_json::read_jsons(instance_directory);
+    let (mut event_driven, _) = read_json::read_jsons(instance_directory);
+    event_driven.topology.config.event_driven = true;
+    let n_steps = count_steps(&mut stepped);
+    let n_events = count_steps(&mut event_driven);
+    assert!(n_events <= n_steps);
+    assert_eq!(stepped.state.arrival_times, event_driven.state.arrival_times, "{}", instance_directory);
+    assert_eq!(stepped.state.entrance_times, event_driven.state.entrance_times, "{}", instance_directory);
+    assert_eq!(stepped.state.time, event_driven.state.time, "{}", instance_directory);
+}
+
+#[test]
+fn test_event_driven_instances(){
+    for instances_directory in ["src/tests/instances/", "src/instances/"]{
+        for entry in std::fs::read_dir(instances_directory).unwrap(){
+            let path = entry.unwrap().path();
+            if path.join("network.json").exists() && path.join("packets.json").exists(){
+                assert_identical_results(&(path.to_str().unwrap().to_owned() + "/"));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_event_driven_long_edges(){
+    let build = |event_driven : bool| NetworkBuilder::new()
+        .edge("s", "v", 100, Fraction::new(1, 3))
+        .edge("v", "t", 50, Fraction::new(2, 3))
+        .packet(0, 0, &["s", "v", "t"])
+        .packet(0, 0, &["s", "v", "t"])
+        .packet(0, 1, &["s", "v", "t"])
+        .packet(0, 400, &["s", "v", "t"])
+        .config(SimulationConfig{event_driven, ..Default::default()})
+        .build().0;
+    let mut stepped = build(false);
+    let mut event_driven = build(true);
+    let n_steps = count_steps(&mut stepped);
+    let n_events = count_steps(&mut event_driven);
+    assert_eq!(stepped.state.arrival_times, event_driven.state.arrival_times);
+    assert_eq!(stepped.state.arrival_times[0], Some(153));
+    assert_eq!(n_steps, 554);
+    assert!(n_events < 30);
+}
+
+#[test]
+fn test_event_driven_run_until(){
+    let (mut network, _) = read_json::read_jsons("src/tests/instances/i_a0,5_b1/");
+    network.topology.config.event_driven = true;
+    network.run_until(1, &mut ());
+    assert_eq!(network.state.time, 1);
+    network.run_until(3, &mut ());
+    assert_eq!(network.state.time, 3);
+    network.run_simulation();
+    assert_eq!(network.state.time, 12);
+}
@@ -0,0 +1,108 @@
+// This file contains tests for limits on the number of packets entering an edge
+#[cfg(test)]
+use {
+    crate::builder::NetworkBuilder,
+    crate::fraction::Fraction,
+    crate::network::SimulationConfig,
+    crate::read_json,
+};
+
+#[test]
+fn test_fractional_inflow_capacity(){
+    for event_driven in [false, true]{
+        // the inflow credit of 1/2 is carried over, so every second step a packet enters
+        let (mut network, _) = NetworkBuilder::new()
+            .edge("s", "t", 1, Fraction::new(3, 1))
+            .inflow_capacity(Fraction::new(1, 2))
+            .packet(0, 0, &["s", "t"])
+            .packet(0, 0, &["s", "t"])
+            .packet(0, 0, &["s", "t"])
+            .packet(0, 0, &["s", "t"])
+            .config(SimulationConfig{event_driven, ..Default::default()})
+            .build();
+        network.run_simulation();
+        assert_eq!(network.state.entrance_times, vec![Some(1), Some(3), Some(5), Some(7)]);
+        assert_eq!(network.state.arrival_times, vec![Some(2), Some(4), Some(6), Some(8)]);
+    }
+}
+
+#[test]
+fn test_inflow_capacity_at_merge(){
+    // both incoming edges deliver a packet per step, but only one packet per step enters v -> t,
+    // alternating between the incoming edges
+    let (mut network, _) = NetworkBuilder::new()
+        .edge("u", "v", 1, Fraction::new(1, 1))
+        .edge("w", "v", 1, Fraction::new(1, 1))
+        .edge("v", "t", 1, Fraction::new(2, 1))
+        .inflow_capacity(Fraction::new(1, 1))
+        .packet(0, 0, &["u", "v", "t"])
+        .packet(0, 0, &["u", "v", "t"])
+        .packet(1, 0, &["w", "v", "t"])
+        .packet(1, 0, &["w", "v", "t"])
+        .build();
+    assert_eq!(network.run_simulation(), None);
+    assert_eq!(network.state.entrance_times, vec![Some(1), Some(3), Some(2), Some(4)]);
+    assert_eq!(network.state.arrival_times, vec![Some(2), Some(4), Some(3), Some(5)]);
+}
+
+#[test]
+fn test_inflow_capacity_and_storage(){
+    // the inflow credit is only used for packets fitting into the edge
+    let (mut network, _) = NetworkBuilder::new()
+        .edge("s", "t", 2, Fraction::new(1, 1))
+        .inflow_capacity(Fraction::new(2, 1))
+        .storage(2)
+        .packet(0, 0, &["s", "t"])
+        .packet(0, 0, &["s", "t"])
+        .packet(0, 0, &["s", "t"])
+        .packet(0, 0, &["s", "t"])
+        .build();
+    network.run_simulation();
+    assert_eq!(network.state.entrance_times, vec![Some(0), Some(0), Some(3), Some(4)]);
+}
+
+#[test]
+fn test_inflow_capacity_from_json(){
+    let network_val = serde_json::json!({"edges": [
+        {"v_from": "s", "v_to": "t", "transit_time": 1, "capacity": {"numerator": 1, "denominator": 1}, "inflow_capacity": {"numerator": 3, "denominator": 2}},
+        {"v_from": "t", "v_to": "u", "transit_time": 1, "capacity": {"numerator": 1, "denominator": 1}},
+    ]});
+    let (vertex_name_to_id, _) = read_json::map_vertex_name_id(&network_val);
+    let (edges, _, _) = read_json::get_network(&network_val, &vertex_name_to_id);
+    assert_eq!(edges[0].inflow_capacity, Some(Fraction::new(3, 2)));
+    assert_eq!(edges[1].inflow_capacity, None);
+}
+
+#[test]
+fn test_inflow_credit_kept_while_full(){
+    for event_driven in [false, true]{
+        // the edge is full every second step, the inflow credit of 1/2 is still carried over, so a packet enters every second step
+        let mut builder = NetworkBuilder::new()
+            .edge("s", "t", 1, Fraction::new(1, 1))
+            .inflow_capacity(Fraction::new(1, 2))
+            .storage(1);
+        for _ in 0..4{
+            builder = builder.packet(0, 0, &["s", "t"]);
+        }
+        let (mut network, _) = builder.config(SimulationConfig{event_driven, ..Default::default()}).build();
+        network.run_simulation();
+        assert_eq!(network.state.entrance_times, vec![Some(1), Some(3), Some(5), Some(7)]);
+    }
+}
+
+#[test]
+fn test_inflow_capacity_behind_slow_edge(){
+    for event_driven in [false, true]{
+        // the packet waits for a -> t after s -> a served it, so the inflow credit of a -> t grows until it fits
+        let (mut network, _) = NetworkBuilder::new()
+            .edge("s", "a", 1, Fraction::new(1, 2))
+            .edge("a", "t", 1, Fraction::new(1, 1))
+            .inflow_capacity(Fraction::new(1, 2))
+            .packet(0, 0, &["s", "a", "t"])
+            .config(SimulationConfig{event_driven, time_horizon : Some(200), ..Default::default()})
+            .build();
+        assert_eq!(network.run_simulation(), None);
+        assert_eq!(network.state.entrance_times, vec![Some(3)]);
+        assert_eq!(network.state.arrival_times, vec![Some(4)]);
+    }
+}
@@ -0,0 +1,84 @@
+// This file contains tests for recording the itineraries of the packets
+#[cfg(test)]
+use {
+    crate::builder::NetworkBuilder,
+    crate::fraction::Fraction,
+    crate::itinerary::{EdgeVisit, ItineraryRecorder},
+    crate::network::{Network, Time},
+    crate::read_json,
+    crate::write_json,
+};
+
+// Path s -> a -> t with transit times 2 and capacities 1 and two packets released at time 0
+#[cfg(test)]
+fn two_packets() -> (Network, std::collections::HashMap<usize, String>){
+    NetworkBuilder::new()
+        .edge("s", "a", 2, Fraction::new(1, 1))
+        .edge("a", "t", 2, Fraction::new(1, 1))
+        .packet(0, 0, &["s", "a", "t"])
+        .packet(0, 0, &["s", "a", "t"])
+        .build()
+}
+
+#[test]
+fn test_itineraries(){
+    let (mut network, _) = two_packets();
+    let mut recorder = ItineraryRecorder::new(&network.topology, &network.state);
+    network.state.run_until(&network.topology, Time::MAX, &mut recorder);
+    assert_eq!(recorder.itineraries[0], vec![
+        EdgeVisit{edge_id : 0, entrance_time : 0, eligible_time : 2, leaving_time : Some(2)},
+        EdgeVisit{edge_id : 1, entrance_time : 2, eligible_time : 4, leaving_time : Some(4)},
+    ]);
+    // the second packet waits one step for the capacity of s -> a
+    assert_eq!(recorder.itineraries[1], vec![
+        EdgeVisit{edge_id : 0, entrance_time : 0, eligible_time : 2, leaving_time : Some(3)},
+        EdgeVisit{edge_id : 1, entrance_time : 3, eligible_time : 5, leaving_time : Some(5)},
+    ]);
+    assert_eq!(recorder.itineraries[1][0].free_flow_time(), 2);
+    assert_eq!(recorder.itineraries[1][0].waiting_time(), Some(1));
+}
+
+#[test]
+fn test_itineraries_zimmer(){
+    for event_driven in [false, true]{
+        let (mut network, _) = read_json::read_jsons("src/tests/instances/zimmer/");
+        network.topology.config.event_driven = event_driven;
+        let mut recorder = ItineraryRecorder::new(&network.topology, &network.state);
+        network.state.run_until(&network.topology, Time::MAX, &mut recorder);
+        for packet in &network.topology.packets{
+            let itinerary = &recorder.itineraries[packet.id];
+            assert_eq!(itinerary.iter().map(|visit| visit.edge_id).collect::<Vec<usize>>(), packet.path);
+            assert!(itinerary[0].entrance_time >= packet.release_time);
+            assert!(itinerary.windows(2).all(|pair| pair[0].leaving_time == Some(pair[1].entrance_time)));
+            assert_eq!(itinerary.last().unwrap().leaving_time, network.state.arrival_times[packet.id]);
+            // the travel time decomposes into waiting before the first edge, transit and waiting on the edges
+            let travel_time = network.state.arrival_times[packet.id].unwrap() - packet.release_time;
+            let edge_time : Time = itinerary.iter().map(|visit| visit.free_flow_time() + visit.waiting_time().unwrap()).sum();
+            assert_eq!(itinerary[0].entrance_time - packet.release_time + edge_time, travel_time);
+        }
+    }
+}
+
+#[test]
+fn test_itineraries_from_state(){
+    // packets already on an edge when recording starts begin with their visit of it
+    let (mut network, _) = two_packets();
+    network.run_until(4, &mut ());
+    let mut recorder = ItineraryRecorder::new(&network.topology, &network.state);
+    network.state.run_until(&network.topology, Time::MAX, &mut recorder);
+    assert_eq!(recorder.itineraries[0], vec![EdgeVisit{edge_id : 1, entrance_time : 2, eligible_time : 4, leaving_time : Some(4)}]);
+    assert_eq!(recorder.itineraries[1], vec![EdgeVisit{edge_id : 1, entrance_time : 3, eligible_time : 5, leaving_time : Some(5)}]);
+}
+
+#[test]
+fn test_itineraries_json(){
+    let (mut network, vertex_id_to_name) = two_packets();
+    let mut recorder = ItineraryRecorder::new(&network.topology, &network.state);
+    network.state.run_until(&network.topology, Time::MAX, &mut recorder);
+    let itineraries_val = write_json::get_itineraries_val(&network.topology, &recorder.itineraries, &vertex_id_to_name);
+    assert_eq!(itineraries_val[1][0]["v_from"], "s");
+    assert_eq!(itineraries_val[1][0]["v_to"], "a");
+    assert_eq!(itineraries_val[1][0]["eligible_time"], 2);
+    assert_eq!(itineraries_val[1][0]["leaving_time"], 3);
+    assert_eq!(itineraries_val[1][1]["entrance_time"], 3);
+}
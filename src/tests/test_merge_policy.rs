@@ -0,0 +1,110 @@
+// This file contains tests for the merge policies at vertices
+#[cfg(test)]
+use {
+    std::sync::Arc,
+    crate::builder::NetworkBuilder,
+    crate::fraction::Fraction,
+    crate::merge_policy::{MergePolicy, merge_policy_from_name},
+    crate::network::{Network, PacketId, SimulationConfig},
+    crate::read_json,
+};
+
+// At time 2 the packets [2, 3] (commodity 1, ready since time 1) leave a-v, [4, 5] (commodity 0, ready at time 2)
+// leave b-v and packet 6 (commodity 0) enters the network at v, all of them heading to c
+#[cfg(test)]
+fn merge_instance(merge_policy : Arc<dyn MergePolicy>) -> Network{
+    let (mut network, _) = NetworkBuilder::new()
+        .edge("a", "v", 1, Fraction::new(2, 1))
+        .edge("b", "v", 2, Fraction::new(4, 1))
+        .edge("v", "c", 5, Fraction::new(1, 1))
+        .packet(1, 0, &["a", "v", "c"])
+        .packet(1, 0, &["a", "v", "c"])
+        .packet(1, 0, &["a", "v", "c"])
+        .packet(1, 0, &["a", "v", "c"])
+        .packet(0, 0, &["b", "v", "c"])
+        .packet(0, 0, &["b", "v", "c"])
+        .packet(0, 2, &["v", "c"])
+        .config(SimulationConfig{merge_policy, ..Default::default()})
+        .build();
+    network.run_until(3, &mut ());
+    network
+}
+
+#[cfg(test)]
+fn merged_order(name : &str) -> Vec<PacketId>{
+    let network = merge_instance(merge_policy_from_name(name).unwrap());
+    // packets 0 and 1 enter v-c alone at time 1
+    assert_eq!(network.state.edge_queues[2].iter().take(2).copied().collect::<Vec<PacketId>>(), vec![0, 1]);
+    network.state.edge_queues[2].iter().skip(2).copied().collect()
+}
+
+#[test]
+fn test_zipper(){
+    assert_eq!(merged_order("zipper"), vec![2, 4, 3, 5, 6]);
+}
+
+#[test]
+fn test_global_fifo(){
+    assert_eq!(merged_order("fifo"), vec![2, 3, 4, 5, 6]);
+}
+
+#[test]
+fn test_commodity_priority(){
+    assert_eq!(merged_order("commodity_priority"), vec![4, 5, 6, 2, 3]);
+}
+
+#[test]
+fn test_round_robin(){
+    assert_eq!(merged_order("round_robin"), vec![2, 4, 6, 3, 5]);
+}
+
+#[test]
+fn test_capacity_weighted_zipper(){
+    // a-v has capacity 2 and b-v capacity 4, so b gets twice the share
+    assert_eq!(merged_order("capacity_weighted_zipper"), vec![4, 2, 5, 3, 6]);
+}
+
+#[test]
+fn test_seeded_random(){
+    let order = merged_order("random:42");
+    assert_eq!(order, merged_order("random:42"));
+    let mut sorted_order = order.clone();
+    sorted_order.sort();
+    assert_eq!(sorted_order, vec![2, 3, 4, 5, 6]);
+    // every queue stays FIFO
+    let position = |packet_id : PacketId| order.iter().position(|id| *id == packet_id).unwrap();
+    assert!(position(2) < position(3));
+    assert!(position(4) < position(5));
+}
+
+#[test]
+fn test_merge_policy_names(){
+    assert!(merge_policy_from_name("random").is_some());
+    assert!(merge_policy_from_name("random:x").is_none());
+    assert!(merge_policy_from_name("zipper:3").is_none());
+    assert!(merge_policy_from_name("unknown").is_none());
+}
+
+#[test]
+fn test_merge_policies_on_instances(){
+    // every policy lets all packets arrive, and the explicit zipper equals the default
+    for name in ["zipper", "fifo", "commodity_priority", "class_priority", "round_robin", "capacity_weighted_zipper", "random:1"]{
+        let (mut default, _) = read_json::read_jsons("src/tests/instances/zimmer/");
+        let (mut network, _) = read_json::read_jsons("src/tests/instances/zimmer/");
+        network.topology.config.merge_policy = merge_policy_from_name(name).unwrap();
+        default.run_simulation();
+        network.run_simulation();
+        assert!(network.state.arrival_times.iter().all(|arrival_time| arrival_time.is_some()), "{}", name);
+        if name == "zipper"{
+            assert_eq!(network.state.arrival_times, default.state.arrival_times);
+        }
+    }
+}
+
+#[test]
+fn test_merge_policy_from_json(){
+    let network_val = serde_json::json!({"edges": [], "merge_policy": "round_robin"});
+    let config = read_json::get_config(&network_val);
+    let network = merge_instance(config.merge_policy);
+    assert_eq!(network.state.edge_queues[2].iter().skip(2).copied().collect::<Vec<PacketId>>(), vec![2, 4, 6, 3, 5]);
+}
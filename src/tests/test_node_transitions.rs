@@ -0,0 +1,70 @@
+// This file contains tests for the transitions of packets through vertices
+#[cfg(test)]
+use {
+    crate::read_json,
+    crate::builder::NetworkBuilder,
+    crate::fraction::Fraction,
+    crate::network::{Network, Topology},
+};
+
+#[test]
+fn test_reference_results(){
+    // the results.json files under src/instances/ were produced by the original implementation
+    for entry in std::fs::read_dir("src/instances/").unwrap(){
+        let path = entry.unwrap().path();
+        if !path.join("network.json").exists(){
+            continue;
+        }
+        let instance_directory = path.to_str().unwrap().to_owned() + "/";
+        let (mut network, _) = read_json::read_jsons(&instance_directory);
+        network.run_simulation();
+        let result_string = std::fs::read_to_string(path.join("results.json")).unwrap();
+        let result_val : serde_json::Value = serde_json::from_str(&result_string).unwrap();
+        let arrival_times : Vec<usize> = result_val["arrival_times"].as_array().unwrap().iter().map(|time| time.as_u64().unwrap() as usize).collect();
+        assert_eq!(network.state.arrival_times.iter().map(|time| time.unwrap()).collect::<Vec<usize>>(), arrival_times, "{}", instance_directory);
+    }
+}
+
+#[test]
+fn test_split_and_merge(){
+    // packets from a and b meet at v and continue to c or d
+    let (mut network, _) = NetworkBuilder::new()
+        .edge("a", "v", 1, Fraction::new(3, 1))
+        .edge("b", "v", 1, Fraction::new(3, 1))
+        .edge("v", "c", 1, Fraction::new(3, 1))
+        .edge("v", "d", 1, Fraction::new(3, 1))
+        .packet(0, 0, &["a", "v", "c"])
+        .packet(0, 0, &["a", "v", "d"])
+        .packet(0, 0, &["a", "v", "c"])
+        .packet(1, 0, &["b", "v", "c"])
+        .packet(1, 0, &["b", "v", "d"])
+        .packet(1, 1, &["v", "c"])
+        .build();
+    network.run_until(2, &mut ());
+    // zipper into v-c: a-queue [0, 2] has priorities 1/2, 1; b-queue [3] and the entering queue [5] have priority 1;
+    // ties are broken by the smaller edge id, with the entering queue last
+    assert_eq!(network.state.edge_queues[2], [0, 2, 3, 5]);
+    assert_eq!(network.state.edge_queues[3], [1, 4]);
+    assert!(network.state.leaving_queues.iter().all(|leaving_queue| leaving_queue.is_empty()));
+    network.run_simulation();
+    assert_eq!(network.state.arrival_times, vec![Some(2), Some(2), Some(2), Some(2), Some(2), Some(3)]);
+}
+
+#[test]
+fn test_unsorted_outgoing_edges(){
+    // vertices built by hand may list their outgoing edges in any order
+    let (network, _) = NetworkBuilder::new()
+        .edge("s", "v", 1, Fraction::new(2, 1))
+        .edge("v", "a", 1, Fraction::new(1, 1))
+        .edge("v", "b", 1, Fraction::new(1, 1))
+        .edge("v", "c", 1, Fraction::new(1, 1))
+        .packet(0, 0, &["s", "v", "c"])
+        .packet(0, 0, &["s", "v", "a"])
+        .build();
+    let mut vertices = network.topology.vertices.clone();
+    vertices[1].outgoing_edges = vec![3, 1, 2];
+    let mut network = Network::new(Topology::new(vertices, network.topology.edges.clone(), network.topology.packets.clone()));
+    assert_eq!(network.topology.vertices[1].outgoing_edges, vec![1, 2, 3]);
+    network.run_simulation();
+    assert_eq!(network.state.arrival_times, vec![Some(2), Some(2)]);
+}
@@ -0,0 +1,85 @@
+// This file contains tests for the step-by-step simulation and observers
+#[cfg(test)]
+use {
+    crate::read_json,
+    crate::observer::Observer,
+    crate::network::{Time, VertexId, EdgeId, PacketId},
+};
+
+#[cfg(test)]
+#[derive(Default)]
+struct EventRecorder{
+    entered : Vec<(PacketId, EdgeId, Time)>,
+    left : Vec<(PacketId, EdgeId, Time)>,
+    merged : Vec<(PacketId, VertexId, Option<EdgeId>, EdgeId, Time)>,
+    arrived : Vec<(PacketId, Time)>,
+}
+
+#[cfg(test)]
+impl Observer for EventRecorder{
+    fn packet_entered_edge(&mut self, packet_id : PacketId, edge_id : EdgeId, time : Time){
+        self.entered.push((packet_id, edge_id, time));
+    }
+
+    fn packet_left_edge(&mut self, packet_id : PacketId, edge_id : EdgeId, time : Time){
+        self.left.push((packet_id, edge_id, time));
+    }
+
+    fn packet_merged(&mut self, packet_id : PacketId, vertex_id : VertexId, incoming_edge_id : Option<EdgeId>, outgoing_edge_id : EdgeId, time : Time){
+        self.merged.push((packet_id, vertex_id, incoming_edge_id, outgoing_edge_id, time));
+    }
+
+    fn packet_arrived(&mut self, packet_id : PacketId, time : Time){
+        self.arrived.push((packet_id, time));
+    }
+}
+
+#[test]
+fn test_observer_i(){
+    let (mut network, _) = read_json::read_jsons("src/tests/instances/i_a1_b1/");
+    let mut recorder = EventRecorder::default();
+    while !network.state.is_finished(&network.topology){
+        network.step(&mut recorder);
+    }
+    assert_eq!(recorder.entered, vec![(0, 0, 2), (1, 0, 2), (2, 0, 3), (3, 0, 3)]);
+    assert_eq!(recorder.merged[0], (0, 0, None, 0, 2));
+    assert_eq!(recorder.left, vec![(0, 0, 3), (1, 0, 4), (2, 0, 5), (3, 0, 6)]);
+    assert_eq!(recorder.arrived, vec![(0, 3), (1, 4), (2, 5), (3, 6)]);
+}
+
+#[test]
+fn test_observer_zimmer(){
+    let (mut network, _) = read_json::read_jsons("src/tests/instances/zimmer/");
+    let mut recorder = EventRecorder::default();
+    network.run_until(Time::MAX, &mut recorder);
+    let n_packets = network.topology.packets.len();
+    assert_eq!(recorder.arrived.len(), n_packets);
+    assert_eq!(recorder.merged.len(), recorder.entered.len());
+    assert_eq!(recorder.left.len(), recorder.entered.len());
+    let total_path_length : usize = network.topology.packets.iter().map(|packet| packet.path.len()).sum();
+    assert_eq!(recorder.entered.len(), total_path_length);
+    for (packet_id, time) in recorder.arrived{
+        assert_eq!(network.state.arrival_times[packet_id], Some(time));
+    }
+}
+
+#[test]
+fn test_run_until(){
+    let (mut stepped, _) = read_json::read_jsons("src/tests/instances/zimmer/");
+    let (mut complete, _) = read_json::read_jsons("src/tests/instances/zimmer/");
+    complete.run_simulation();
+    stepped.run_until(7, &mut ());
+    assert_eq!(stepped.state.time, 7);
+    for (packet_id, arrival_time) in stepped.state.arrival_times.iter().enumerate(){
+        match complete.state.arrival_times[packet_id].unwrap() < 7{
+            true => assert_eq!(*arrival_time, complete.state.arrival_times[packet_id]),
+            false => assert_eq!(*arrival_time, None),
+        }
+    }
+    stepped.run_until_arrived(3, &mut ());
+    assert_eq!(stepped.state.arrival_times[3], complete.state.arrival_times[3]);
+    assert_eq!(stepped.state.time, complete.state.arrival_times[3].unwrap() + 1); // the time step of the arrival is completed
+    stepped.run_until(Time::MAX, &mut ());
+    assert_eq!(stepped.state.arrival_times, complete.state.arrival_times);
+    assert_eq!(stepped.state.time, complete.state.time);
+}
@@ -0,0 +1,99 @@
+// This file contains tests for packets taking more or less than one unit of capacity
+#[cfg(test)]
+use {
+    std::collections::VecDeque,
+    crate::builder::NetworkBuilder,
+    crate::fraction::Fraction,
+    crate::merge_policy::zipper_order,
+    crate::network::SimulationConfig,
+    crate::read_json,
+    crate::service_model::service_model_from_name,
+};
+
+#[test]
+fn test_large_packet(){
+    for event_driven in [false, true]{
+        // the truck of size 2 waits until the edge of capacity 1 has carried over a credit of 2
+        let (mut network, _) = NetworkBuilder::new()
+            .edge("s", "t", 1, Fraction::new(1, 1))
+            .packet(0, 0, &["s", "t"])
+            .size(Fraction::new(2, 1))
+            .packet(0, 0, &["s", "t"])
+            .config(SimulationConfig{event_driven, ..Default::default()})
+            .build();
+        network.run_simulation();
+        assert_eq!(network.state.arrival_times, vec![Some(2), Some(3)]);
+    }
+}
+
+#[test]
+fn test_small_packets(){
+    // two packets of size 1/2 leave the edge of capacity 1 per time step
+    let mut builder = NetworkBuilder::new().edge("s", "t", 1, Fraction::new(1, 1));
+    for _ in 0..4{
+        builder = builder.packet(0, 0, &["s", "t"]).size(Fraction::new(1, 2));
+    }
+    let (mut network, _) = builder.build();
+    network.run_simulation();
+    assert_eq!(network.state.arrival_times, vec![Some(1), Some(1), Some(2), Some(2)]);
+}
+
+#[test]
+fn test_sizes_with_token_bucket(){
+    // starting with 1 token, the bucket of burst 3 holds enough tokens for the truck of size 3 at time 2
+    let (mut network, _) = NetworkBuilder::new()
+        .edge("s", "t", 1, Fraction::new(1, 1))
+        .packet(0, 0, &["s", "t"])
+        .size(Fraction::new(3, 1))
+        .config(SimulationConfig{service_model : service_model_from_name("token_bucket:3").unwrap(), ..Default::default()})
+        .build();
+    network.run_simulation();
+    assert_eq!(network.state.arrival_times, vec![Some(2)]);
+}
+
+#[test]
+fn test_zipper_with_sizes(){
+    // queue 0 holds a truck of size 2 and a car, queue 1 two cars: the truck only gets its turn
+    // after the first car of queue 1, as it takes 2/3 of its queue
+    let queues = vec![VecDeque::from(vec![0, 1]), VecDeque::from(vec![2, 3])];
+    let sizes = [Fraction::new(2, 1), Fraction::new(1, 1), Fraction::new(1, 1), Fraction::new(1, 1)];
+    assert_eq!(zipper_order(&queues, |queue_id| queue_id, |packet_id| sizes[packet_id]), vec![1, 0, 0, 1]);
+    // with unit sizes the queues alternate
+    assert_eq!(zipper_order(&queues, |queue_id| queue_id, |_| Fraction::new(1, 1)), vec![0, 1, 0, 1]);
+}
+
+#[test]
+fn test_sizes_at_inflow_and_vertex_capacity(){
+    // the inflow capacity of 1 admits two packets of size 1/2 per step, the vertex capacity of 1 lets pass
+    // one packet of size 1 per step
+    let (mut network, _) = NetworkBuilder::new()
+        .edge("s", "v", 1, Fraction::new(4, 1))
+        .inflow_capacity(Fraction::new(1, 1))
+        .edge("v", "t", 1, Fraction::new(4, 1))
+        .vertex_capacity("v", Fraction::new(1, 1))
+        .packet(0, 0, &["s", "v", "t"])
+        .size(Fraction::new(1, 2))
+        .packet(0, 0, &["s", "v", "t"])
+        .size(Fraction::new(1, 2))
+        .packet(0, 0, &["s", "v", "t"])
+        .build();
+    network.run_simulation();
+    assert_eq!(network.state.arrival_times, vec![Some(2), Some(2), Some(3)]);
+}
+
+#[test]
+fn test_sizes_from_json(){
+    let network_val = serde_json::json!({"edges": [
+        {"v_from": "s", "v_to": "t", "transit_time": 1, "capacity": {"numerator": 1, "denominator": 1}},
+    ]});
+    let packets_val = serde_json::json!({"packets": [
+        {"commodity_id": 0, "release_time": 0, "path": ["s", "t"], "size": 3},
+        {"commodity_id": 0, "release_time": 0, "path": ["s", "t"], "size": {"numerator": 1, "denominator": 2}},
+        {"commodity_id": 0, "release_time": 0, "path": ["s", "t"]},
+    ]});
+    let (vertex_name_to_id, _) = read_json::map_vertex_name_id(&network_val);
+    let (_, _, edge_to_id) = read_json::get_network(&network_val, &vertex_name_to_id);
+    let packets = read_json::get_packets(&packets_val, &vertex_name_to_id, &edge_to_id);
+    let sizes : Vec<Fraction> = packets.iter().map(|packet| packet.size).collect();
+    assert_eq!(sizes, vec![Fraction::new(3, 1), Fraction::new(1, 2), Fraction::new(1, 1)]);
+}
@@ -0,0 +1,86 @@
+// This file contains tests for priority classes of commodities and the statistics per class
+#[cfg(test)]
+use {
+    crate::builder::NetworkBuilder,
+    crate::fraction::Fraction,
+    crate::merge_policy::merge_policy_from_name,
+    crate::network::{Network, PacketId, SimulationConfig},
+    crate::read_json,
+    crate::statistics::{ClassStatistics, class_statistics},
+    crate::write_json,
+};
+
+// At time 1 the packets [0, 1, 2] leave a-v and [3, 4] leave b-v, all heading to c.
+// Commodity 1, i.e. all packets but packet 1, has priority class 1.
+#[cfg(test)]
+fn class_instance(merge_policy : &str) -> Network{
+    let (network, _) = NetworkBuilder::new()
+        .edge("a", "v", 1, Fraction::new(3, 1))
+        .edge("b", "v", 1, Fraction::new(2, 1))
+        .edge("v", "c", 5, Fraction::new(1, 1))
+        .packet(1, 0, &["a", "v", "c"])
+        .packet(0, 0, &["a", "v", "c"])
+        .packet(1, 0, &["a", "v", "c"])
+        .packet(1, 0, &["b", "v", "c"])
+        .packet(1, 0, &["b", "v", "c"])
+        .priority_class(1, 1)
+        .config(SimulationConfig{merge_policy : merge_policy_from_name(merge_policy).unwrap(), ..Default::default()})
+        .build();
+    network
+}
+
+#[test]
+fn test_class_priority(){
+    let mut network = class_instance("class_priority");
+    network.run_until(2, &mut ());
+    // packet 1 only waits for packet 0 ahead of it, all other packets of class 1 overtake it
+    // and are zipped among themselves
+    assert_eq!(network.state.edge_queues[2].iter().copied().collect::<Vec<PacketId>>(), vec![0, 3, 4, 1, 2]);
+    // the zipper ignores the classes
+    let mut network = class_instance("zipper");
+    network.run_until(2, &mut ());
+    assert_eq!(network.state.edge_queues[2].iter().copied().collect::<Vec<PacketId>>(), vec![0, 3, 1, 2, 4]);
+}
+
+#[test]
+fn test_class_statistics(){
+    let mut network = class_instance("class_priority");
+    network.run_simulation();
+    assert_eq!(network.state.arrival_times, vec![Some(6), Some(9), Some(10), Some(7), Some(8)]);
+    assert_eq!(class_statistics(&network.topology, &network.state), vec![
+        ClassStatistics{priority_class : 0, n_packets : 1, n_arrived : 1, n_dropped : 0, mean_travel_time : Some(9.0), max_travel_time : Some(9)},
+        ClassStatistics{priority_class : 1, n_packets : 4, n_arrived : 4, n_dropped : 0, mean_travel_time : Some(7.75), max_travel_time : Some(10)},
+    ]);
+}
+
+#[test]
+fn test_priority_classes_from_json(){
+    let network_val = serde_json::json!({"edges": [
+        {"v_from": "a", "v_to": "v", "transit_time": 1, "capacity": {"numerator": 1, "denominator": 1}},
+        {"v_from": "b", "v_to": "v", "transit_time": 1, "capacity": {"numerator": 1, "denominator": 1}},
+        {"v_from": "v", "v_to": "t", "transit_time": 1, "capacity": {"numerator": 1, "denominator": 1}},
+    ]});
+    let packets_val = serde_json::json!({
+        "commodities": [{"id": 1, "priority_class": 2}],
+        "packets": [
+            {"commodity_id": 0, "release_time": 0, "path": ["a", "v", "t"]},
+            {"commodity_id": 1, "release_time": 0, "path": ["b", "v", "t"]},
+        ],
+    });
+    let (vertex_name_to_id, vertex_id_to_name) = read_json::map_vertex_name_id(&network_val);
+    let (edges, vertices, edge_to_id) = read_json::get_network(&network_val, &vertex_name_to_id);
+    let packets = read_json::get_packets(&packets_val, &vertex_name_to_id, &edge_to_id);
+    let mut network = Network::new(crate::network::Topology::new(vertices, edges, packets));
+    // a commodity only giving its class is no commodity of the equilibrium problem
+    assert!(read_json::get_commodities(&packets_val, &network.topology, &vertex_name_to_id, &edge_to_id).is_empty());
+    network.topology.config.merge_policy = merge_policy_from_name("class_priority").unwrap();
+    network.reset();
+    network.run_simulation();
+    // the zipper would let packet 0 go first because a-v has the smaller edge id
+    assert_eq!(network.state.arrival_times, vec![Some(3), Some(2)]);
+    let output_val = write_json::get_output_val(&network.topology, &network.state, vertex_id_to_name);
+    assert_eq!(output_val["priority_classes"], serde_json::json!([0, 2]));
+    assert_eq!(output_val["class_statistics"][1], serde_json::json!(
+        {"priority_class": 2, "n_packets": 1, "n_arrived": 1, "n_dropped": 0, "mean_travel_time": 2.0, "max_travel_time": 2}
+    ));
+}
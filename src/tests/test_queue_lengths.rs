@@ -0,0 +1,98 @@
+// This file contains tests for recording the queue lengths of the edges over time
+#[cfg(test)]
+use {
+    crate::builder::NetworkBuilder,
+    crate::fraction::Fraction,
+    crate::itinerary::ItineraryRecorder,
+    crate::network::{Network, Time},
+    crate::queue_lengths::{QueueLengthRecorder, QueueSample},
+    crate::read_json,
+    crate::write_json,
+};
+
+// Path s -> a -> t with transit times 2 and capacities 1 and two packets released at time 0
+#[cfg(test)]
+fn two_packets(event_driven : bool) -> (Network, std::collections::HashMap<usize, String>){
+    let (mut network, vertex_id_to_name) = NetworkBuilder::new()
+        .edge("s", "a", 2, Fraction::new(1, 1))
+        .edge("a", "t", 2, Fraction::new(1, 1))
+        .packet(0, 0, &["s", "a", "t"])
+        .packet(0, 0, &["s", "a", "t"])
+        .build();
+    network.topology.config.event_driven = event_driven;
+    (network, vertex_id_to_name)
+}
+
+#[cfg(test)]
+fn samples(lengths : &[(Time, usize, usize)]) -> Vec<QueueSample>{
+    lengths.iter().map(|(time, n_queued, n_on_edge)| QueueSample{time : *time, n_queued : *n_queued, n_on_edge : *n_on_edge}).collect()
+}
+
+#[test]
+fn test_queue_lengths(){
+    for event_driven in [false, true]{
+        let (mut network, _) = two_packets(event_driven);
+        let mut recorder = QueueLengthRecorder::new(&network.topology, false);
+        network.run_until(Time::MAX, &mut recorder);
+        // the second packet completes its transit over s -> a at time 2 but leaves only at time 3
+        assert_eq!(recorder.series[0], samples(&[(0, 0, 2), (1, 0, 2), (2, 1, 1), (3, 0, 0), (4, 0, 0), (5, 0, 0)]));
+        assert_eq!(recorder.series[1], samples(&[(0, 0, 0), (1, 0, 0), (2, 0, 1), (3, 0, 2), (4, 0, 1), (5, 0, 0)]));
+    }
+}
+
+#[test]
+fn test_queue_lengths_decreasing_transit_time(){
+    // packets 2 and 3 complete their transit before packet 0, but wait behind it in transit until time 5
+    for event_driven in [false, true]{
+        let mut builder = NetworkBuilder::new()
+            .edge("s", "t", 1, Fraction::new(10, 1))
+            .transit_time_schedule(vec![(0, 5), (2, 1)]);
+        for release_time in 0..4{
+            builder = builder.packet(0, release_time, &["s", "t"]);
+        }
+        let (mut network, _) = builder.build();
+        network.topology.config.event_driven = event_driven;
+        let mut recorder = QueueLengthRecorder::new(&network.topology, false);
+        network.run_until(Time::MAX, &mut recorder);
+        assert_eq!(network.state.arrival_times, vec![Some(5), Some(6), Some(6), Some(6)]);
+        assert_eq!(recorder.series[0], samples(&[(0, 0, 1), (1, 0, 2), (2, 0, 3), (3, 0, 4), (4, 0, 4), (5, 0, 3), (6, 0, 0)]));
+    }
+}
+
+#[test]
+fn test_queue_length_changes(){
+    let (mut network, _) = two_packets(false);
+    let mut recorder = QueueLengthRecorder::new(&network.topology, true);
+    network.run_until(Time::MAX, &mut recorder);
+    assert_eq!(recorder.series[0], samples(&[(0, 0, 2), (2, 1, 1), (3, 0, 0)]));
+    assert_eq!(recorder.series[1], samples(&[(0, 0, 0), (2, 0, 1), (3, 0, 2), (4, 0, 1), (5, 0, 0)]));
+}
+
+#[test]
+fn test_queue_lengths_event_driven(){
+    // skipped time steps do not change the recorded series
+    for only_changes in [false, true]{
+        let (mut network, _) = read_json::read_jsons("src/tests/instances/zimmer/");
+        let mut recorder = QueueLengthRecorder::new(&network.topology, only_changes);
+        network.run_until(Time::MAX, &mut recorder);
+        network.topology.config.event_driven = true;
+        network.reset();
+        // a pair of observers notifies both
+        let mut recorders = (None::<ItineraryRecorder>, QueueLengthRecorder::new(&network.topology, only_changes));
+        network.run_until(Time::MAX, &mut recorders);
+        assert_eq!(recorders.1.series, recorder.series);
+    }
+}
+
+#[test]
+fn test_queue_lengths_json(){
+    let (mut network, vertex_id_to_name) = two_packets(false);
+    let mut recorder = QueueLengthRecorder::new(&network.topology, true);
+    network.run_until(Time::MAX, &mut recorder);
+    let queue_lengths_val = write_json::get_queue_lengths_val(&network.topology, &recorder.series, &vertex_id_to_name);
+    assert_eq!(queue_lengths_val[0]["v_from"], "s");
+    assert_eq!(queue_lengths_val[0]["v_to"], "a");
+    assert_eq!(queue_lengths_val[0]["times"], serde_json::json!([0, 2, 3]));
+    assert_eq!(queue_lengths_val[0]["n_queued"], serde_json::json!([0, 1, 0]));
+    assert_eq!(queue_lengths_val[0]["n_on_edge"], serde_json::json!([2, 1, 0]));
+}
@@ -0,0 +1,45 @@
+// This file contains tests for the release schedule of packets
+#[cfg(test)]
+use {
+    crate::read_json,
+    crate::builder::NetworkBuilder,
+    crate::fraction::Fraction,
+};
+
+#[test]
+fn test_release_schedule(){
+    let (network, _) = NetworkBuilder::new()
+        .edge("s", "t", 1, Fraction::new(1, 1))
+        .edge("v", "t", 1, Fraction::new(1, 1))
+        .packet(0, 3, &["s", "t"])
+        .packet(1, 1, &["v", "t"])
+        .packet(0, 1, &["s", "t"])
+        .packet(1, 1, &["v", "t"])
+        .build();
+    let release_schedule = &network.topology.release_schedule;
+    assert_eq!(release_schedule.released(1, 0), &[2]);
+    assert_eq!(release_schedule.released(1, 1), &[1, 3]);
+    assert_eq!(release_schedule.released(3, 0), &[0]);
+    assert!(release_schedule.released(3, 1).is_empty());
+    assert!(release_schedule.released(2, 0).is_empty());
+    assert_eq!(release_schedule.next_release_time(0), Some(1));
+    assert_eq!(release_schedule.next_release_time(1), Some(1));
+    assert_eq!(release_schedule.next_release_time(2), Some(3));
+    assert_eq!(release_schedule.next_release_time(4), None);
+}
+
+#[test]
+fn test_release_schedule_instance(){
+    let (network, _) = read_json::read_jsons("src/tests/instances/zimmer/");
+    let mut n_released = 0;
+    for time in 0..=network.topology.packets.iter().map(|packet| packet.release_time).max().unwrap(){
+        for edge in &network.topology.edges{
+            for packet_id in network.topology.release_schedule.released(time, edge.id){
+                assert_eq!(network.topology.packets[*packet_id].release_time, time);
+                assert_eq!(network.topology.packets[*packet_id].path[0], edge.id);
+                n_released += 1;
+            }
+        }
+    }
+    assert_eq!(n_released, network.topology.packets.len());
+}
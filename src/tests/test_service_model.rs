@@ -0,0 +1,91 @@
+// This file contains tests for the service models of edges
+#[cfg(test)]
+use {
+    crate::builder::NetworkBuilder,
+    crate::fraction::Fraction,
+    crate::network::{Network, Time, SimulationConfig},
+    crate::schedule::Schedule,
+    crate::service_model::{ServiceModel, TokenBucket, service_model_from_name},
+};
+
+// Single edge with transit time 1, packets released at the given times
+#[cfg(test)]
+fn single_edge(capacity : Fraction, release_times : &[Time], service_model : &str, event_driven : bool) -> Network{
+    let mut builder = NetworkBuilder::new().edge("s", "t", 1, capacity);
+    for release_time in release_times{
+        builder = builder.packet(0, *release_time, &["s", "t"]);
+    }
+    let service_model = service_model_from_name(service_model).unwrap();
+    builder.config(SimulationConfig{service_model, event_driven, ..Default::default()}).build().0
+}
+
+#[cfg(test)]
+fn arrival_times(capacity : Fraction, release_times : &[Time], service_model : &str) -> Vec<Time>{
+    let mut stepped = single_edge(capacity, release_times, service_model, false);
+    let mut event_driven = single_edge(capacity, release_times, service_model, true);
+    stepped.run_simulation();
+    event_driven.run_simulation();
+    assert_eq!(stepped.state.arrival_times, event_driven.state.arrival_times, "{}", service_model);
+    stepped.state.arrival_times.iter().map(|arrival_time| arrival_time.unwrap()).collect()
+}
+
+#[test]
+fn test_carry_over(){
+    assert_eq!(arrival_times(Fraction::new(1, 2), &[0, 0, 0, 0], "carry_over"), vec![2, 4, 6, 8]);
+    assert_eq!(arrival_times(Fraction::new(3, 2), &[0, 0, 0, 0], "carry_over"), vec![1, 2, 2, 3]);
+    assert_eq!(arrival_times(Fraction::new(1, 1), &[0, 0, 5, 5, 5], "carry_over"), vec![1, 2, 6, 7, 8]);
+}
+
+#[test]
+fn test_no_carry_over(){
+    assert_eq!(arrival_times(Fraction::new(3, 2), &[0, 0, 0, 0], "no_carry_over"), vec![1, 2, 3, 4]);
+    assert_eq!(arrival_times(Fraction::new(2, 1), &[0, 0, 0], "no_carry_over"), vec![1, 1, 2]);
+}
+
+#[test]
+fn test_token_bucket(){
+    // tokens accumulate while the edge is idle, including time 0
+    assert_eq!(arrival_times(Fraction::new(1, 1), &[0, 0, 5, 5, 5], "token_bucket:3"), vec![1, 1, 6, 6, 6]);
+    assert_eq!(arrival_times(Fraction::new(1, 1), &[0, 0, 5, 5, 5], "token_bucket:2"), vec![1, 1, 6, 6, 7]);
+    assert_eq!(arrival_times(Fraction::new(1, 2), &[0, 0, 0], "token_bucket:1"), vec![1, 3, 5]);
+}
+
+#[test]
+fn test_token_bucket_skip_idle(){
+    // the closed form refills the bucket like serving no packet in every idle time step
+    let bucket = TokenBucket{burst : Fraction::new(7, 2)};
+    let capacity = Schedule::new(vec![(0, Fraction::new(1, 3)), (4, Fraction::new(0, 1)), (9, Fraction::new(2, 5)), (20, Fraction::new(3, 1))]);
+    for from in 0..25{
+        for to in from..25{
+            for credit in [Fraction::new(0, 1), Fraction::new(5, 4), Fraction::new(7, 2)]{
+                let mut skipped = credit;
+                bucket.skip_idle(&mut skipped, from, to, &capacity);
+                let mut stepped = credit;
+                for time in from..to{
+                    bucket.serve(&mut stepped, &[], time, capacity.at(time + 1));
+                }
+                assert_eq!(skipped, stepped, "{} to {} from {}", from, to, credit);
+            }
+        }
+    }
+    // long idle phases take a single step per piece of the schedule
+    let mut credit = Fraction::new(0, 1);
+    bucket.skip_idle(&mut credit, 0, Time::MAX - 1, &Schedule::constant(Fraction::new(1, 1000)));
+    assert_eq!(credit, bucket.burst);
+}
+
+#[test]
+fn test_periodic(){
+    // capacity 2/3 offers 0, 1, 1, 0, 1, 1, 0, ... slots at times 0, 1, 2, ...
+    assert_eq!(arrival_times(Fraction::new(2, 3), &[0, 0, 0, 0], "periodic"), vec![1, 2, 4, 5]);
+    assert_eq!(arrival_times(Fraction::new(2, 3), &[0, 10], "periodic"), vec![1, 11]);
+    assert_eq!(arrival_times(Fraction::new(2, 3), &[1, 11], "periodic"), vec![2, 13]);
+}
+
+#[test]
+fn test_service_model_names(){
+    assert!(service_model_from_name("carry_over").is_some());
+    assert!(service_model_from_name("token_bucket:5/2").is_some());
+    assert!(service_model_from_name("token_bucket").is_none());
+    assert!(service_model_from_name("periodic:2").is_none());
+}
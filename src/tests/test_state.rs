@@ -0,0 +1,35 @@
+// This file contains tests for simulating the same topology repeatedly
+#[cfg(test)]
+use {
+    crate::read_json,
+    crate::network::SimulationState,
+};
+
+#[test]
+fn test_reset(){
+    let (mut network, _) = read_json::read_jsons("src/tests/instances/zimmer/");
+    network.run_simulation();
+    let first_arrival_times = network.state.arrival_times.clone();
+    let first_time = network.state.time;
+    network.reset();
+    assert_eq!(network.state.time, 0);
+    assert_eq!(network.state.packets_arrived, 0);
+    network.run_simulation();
+    assert_eq!(network.state.arrival_times, first_arrival_times);
+    assert_eq!(network.state.time, first_time);
+}
+
+#[test]
+fn test_independent_states(){
+    let (network, _) = read_json::read_jsons("src/tests/instances/y_a1_b0,5/");
+    let fresh_state = SimulationState::new(&network.topology);
+    let mut state_a = fresh_state.clone();
+    let mut state_b = fresh_state.clone();
+    state_a.run_simulation(&network.topology);
+    assert_eq!(fresh_state.packets_arrived, 0);
+    assert_eq!(state_b.packets_arrived, 0);
+    state_b.run_simulation(&network.topology);
+    assert_eq!(state_a.arrival_times, state_b.arrival_times);
+    assert_eq!(state_a.entrance_times, state_b.entrance_times);
+    assert_eq!(state_a.packets_arrived, network.topology.packets.len());
+}
@@ -0,0 +1,128 @@
+// This file contains tests for edges with finite storage and the resulting spillback
+#[cfg(test)]
+use {
+    crate::builder::NetworkBuilder,
+    crate::fraction::Fraction,
+    crate::network::{Network, SimulationConfig, EdgeId, PacketId, Time},
+    crate::observer::Observer,
+    crate::read_json,
+};
+
+// Chain s -> a -> t where a -> t holds at most two packets, four packets start at time 0
+#[cfg(test)]
+fn chain(event_driven : bool, storage : Option<usize>) -> Network{
+    let mut builder = NetworkBuilder::new()
+        .edge("s", "a", 1, Fraction::new(2, 1))
+        .edge("a", "t", 3, Fraction::new(1, 1));
+    if let Some(storage) = storage{
+        builder = builder.storage(storage);
+    }
+    for _ in 0..4{
+        builder = builder.packet(0, 0, &["s", "a", "t"]);
+    }
+    builder.config(SimulationConfig{event_driven, ..Default::default()}).build().0
+}
+
+#[cfg(test)]
+#[derive(Default)]
+struct LeftRecorder{
+    left : Vec<(PacketId, EdgeId, Time)>,
+}
+
+#[cfg(test)]
+impl Observer for LeftRecorder{
+    fn packet_left_edge(&mut self, packet_id : PacketId, edge_id : EdgeId, time : Time){
+        self.left.push((packet_id, edge_id, time));
+    }
+}
+
+#[test]
+fn test_unbounded_storage(){
+    let mut network = chain(false, None);
+    network.run_simulation();
+    assert_eq!(network.state.arrival_times, vec![Some(4), Some(5), Some(6), Some(7)]);
+}
+
+#[test]
+fn test_spillback(){
+    for event_driven in [false, true]{
+        let mut network = chain(event_driven, Some(2));
+        let mut recorder = LeftRecorder::default();
+        network.run_until(Time::MAX, &mut recorder);
+        // the last two packets wait on s -> a until a -> t has space again
+        assert_eq!(network.state.arrival_times, vec![Some(4), Some(5), Some(8), Some(9)]);
+        assert!(recorder.left.contains(&(2, 0, 5)));
+        assert!(recorder.left.contains(&(3, 0, 6)));
+    }
+}
+
+#[test]
+fn test_blocked_packets_occupy_storage(){
+    // s -> a is full while its packets wait for a -> t, so packets released later wait at s
+    let (mut network, _) = NetworkBuilder::new()
+        .edge("s", "a", 1, Fraction::new(1, 1))
+        .storage(1)
+        .edge("a", "t", 2, Fraction::new(1, 1))
+        .storage(1)
+        .packet(0, 0, &["s", "a", "t"])
+        .packet(0, 0, &["s", "a", "t"])
+        .packet(0, 0, &["s", "a", "t"])
+        .build();
+    network.run_until(4, &mut ());
+    assert_eq!(network.state.source_queues[0], vec![2]);
+    assert_eq!(network.state.leaving_queues[0], vec![1]);
+    network.run_simulation();
+    assert_eq!(network.state.arrival_times, vec![Some(3), Some(6), Some(9)]);
+}
+
+#[test]
+fn test_blocked_packets_use_capacity_once(){
+    for (capacity, entrance_times) in [(Fraction::new(1, 2), vec![Some(2), Some(7)]), (Fraction::new(1, 3), vec![Some(3), Some(8)])]{
+        for event_driven in [false, true]{
+            // packet 1 leaves s -> a before a -> t has space again and waits without using the capacity of s -> a again
+            let (mut network, _) = NetworkBuilder::new()
+                .edge("s", "a", 1, capacity)
+                .edge("a", "t", 4, Fraction::new(1, 1))
+                .storage(1)
+                .packet(0, 0, &["s", "a", "t"])
+                .packet(0, 0, &["s", "a", "t"])
+                .config(SimulationConfig{event_driven, ..Default::default()})
+                .build();
+            assert_eq!(network.run_simulation(), None);
+            assert_eq!(network.state.entrance_times, entrance_times);
+        }
+    }
+}
+
+#[test]
+fn test_zipper_admits_what_fits(){
+    for event_driven in [false, true]{
+        let (mut network, _) = NetworkBuilder::new()
+            .edge("u", "v", 1, Fraction::new(2, 1))
+            .edge("w", "v", 1, Fraction::new(2, 1))
+            .edge("v", "t", 5, Fraction::new(1, 1))
+            .storage(2)
+            .packet(0, 0, &["u", "v", "t"])
+            .packet(0, 0, &["u", "v", "t"])
+            .packet(1, 0, &["w", "v", "t"])
+            .packet(1, 0, &["w", "v", "t"])
+            .config(SimulationConfig{event_driven, ..Default::default()})
+            .build();
+        network.run_simulation();
+        // the zipper alternates between both incoming edges, but only two packets fit at time 1
+        assert_eq!(network.state.entrance_times, vec![Some(1), Some(7), Some(1), Some(8)]);
+        assert_eq!(network.state.arrival_times, vec![Some(6), Some(12), Some(7), Some(13)]);
+    }
+}
+
+#[test]
+fn test_storage_from_json(){
+    let network_val = serde_json::json!({"edges": [
+        {"v_from": "s", "v_to": "t", "transit_time": 1, "capacity": {"numerator": 1, "denominator": 1}, "storage": 3},
+        {"v_from": "t", "v_to": "u", "transit_time": 1, "capacity": {"numerator": 1, "denominator": 1}},
+    ]});
+    let (vertex_name_to_id, _) = read_json::map_vertex_name_id(&network_val);
+    let (edges, _, _) = read_json::get_network(&network_val, &vertex_name_to_id);
+    assert_eq!(edges[0].storage, Some(3));
+    assert_eq!(edges[1].storage, None);
+}
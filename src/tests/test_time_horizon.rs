@@ -0,0 +1,79 @@
+// This file contains tests for stopping simulations at a time horizon and reporting their partial results
+#[cfg(test)]
+use {
+    crate::builder::NetworkBuilder,
+    crate::fraction::Fraction,
+    crate::network::{Network, SimulationConfig},
+    crate::read_json,
+    crate::statistics::{InFlightPacket, Summary, in_flight_packets, summary},
+    crate::write_json,
+};
+
+// Path s -> a -> t with transit times 2 and packets released at times 0, 2 and 10
+#[cfg(test)]
+fn horizon_instance(time_horizon : Option<usize>, event_driven : bool) -> (Network, std::collections::HashMap<usize, String>){
+    NetworkBuilder::new()
+        .edge("s", "a", 2, Fraction::new(1, 1))
+        .edge("a", "t", 2, Fraction::new(1, 1))
+        .packet(0, 0, &["s", "a", "t"])
+        .packet(0, 2, &["s", "a", "t"])
+        .packet(0, 10, &["s", "a", "t"])
+        .config(SimulationConfig{time_horizon, event_driven, ..Default::default()})
+        .build()
+}
+
+#[test]
+fn test_time_horizon(){
+    for event_driven in [false, true]{
+        let (mut network, _) = horizon_instance(Some(5), event_driven);
+        assert_eq!(network.run_simulation(), None);
+        assert_eq!(network.state.time, 5);
+        assert_eq!(network.state.arrival_times, vec![Some(4), None, None]);
+        assert_eq!(in_flight_packets(&network.topology, &network.state), vec![InFlightPacket{packet_id : 1, edge_id : 1, path_position : 1}]);
+        let summary = summary(&network.topology, &network.state);
+        assert_eq!(summary, Summary{time : 5, n_packets : 3, n_arrived : 1, n_dropped : 0, n_in_flight : 1, n_waiting : 0, n_unreleased : 1});
+        assert_eq!(summary.n_pending(), 2);
+        // without horizon all packets arrive
+        let (mut network, _) = horizon_instance(None, event_driven);
+        network.run_simulation();
+        assert_eq!(network.state.arrival_times, vec![Some(4), Some(6), Some(14)]);
+    }
+}
+
+#[test]
+fn test_time_horizon_until_arrived(){
+    let (mut network, _) = horizon_instance(Some(5), false);
+    network.run_until_arrived(2, &mut ());
+    assert_eq!(network.state.time, 5);
+    assert_eq!(network.state.arrival_times[2], None);
+}
+
+#[test]
+fn test_waiting_packets_at_horizon(){
+    // the second packet waits for the storage of s -> t
+    let (mut network, _) = NetworkBuilder::new()
+        .edge("s", "t", 3, Fraction::new(1, 1))
+        .storage(1)
+        .packet(0, 0, &["s", "t"])
+        .packet(0, 0, &["s", "t"])
+        .config(SimulationConfig{time_horizon : Some(2), ..Default::default()})
+        .build();
+    network.run_simulation();
+    let summary = summary(&network.topology, &network.state);
+    assert_eq!((summary.n_in_flight, summary.n_waiting, summary.n_unreleased), (1, 1, 0));
+}
+
+#[test]
+fn test_partial_results_json(){
+    let (mut network, vertex_id_to_name) = horizon_instance(Some(5), false);
+    network.run_simulation();
+    let output_val = write_json::get_output_val(&network.topology, &network.state, vertex_id_to_name);
+    assert_eq!(output_val["arrival_times"], serde_json::json!([4, null, null]));
+    assert_eq!(output_val["travel_times"], serde_json::json!([4, null, null]));
+    assert_eq!(output_val["in_flight_packets"], serde_json::json!([{"packet_id": 1, "v_from": "a", "v_to": "t", "path_position": 1}]));
+    assert_eq!(output_val["summary"], serde_json::json!(
+        {"time": 5, "n_packets": 3, "n_arrived": 1, "n_dropped": 0, "n_pending": 2, "n_in_flight": 1, "n_waiting": 0, "n_unreleased": 1}
+    ));
+    assert_eq!(read_json::get_config(&serde_json::json!({"time_horizon": 5})).time_horizon, Some(5));
+    assert_eq!(read_json::get_config(&serde_json::json!({})).time_horizon, None);
+}
@@ -0,0 +1,46 @@
+// This file contains tests for transit times depending on the entrance time
+#[cfg(test)]
+use {
+    crate::builder::NetworkBuilder,
+    crate::fraction::Fraction,
+    crate::network::{Time, SimulationConfig},
+    crate::read_json,
+    crate::schedule::Schedule,
+};
+
+#[cfg(test)]
+fn arrival_times(transit_times : Vec<(Time, usize)>, release_times : &[Time]) -> Vec<Option<Time>>{
+    let mut results = Vec::<Vec<Option<Time>>>::new();
+    for event_driven in [false, true]{
+        let mut builder = NetworkBuilder::new()
+            .edge("s", "t", 1, Fraction::new(10, 1))
+            .transit_time_schedule(transit_times.clone());
+        for release_time in release_times{
+            builder = builder.packet(0, *release_time, &["s", "t"]);
+        }
+        let (mut network, _) = builder.config(SimulationConfig{event_driven, ..Default::default()}).build();
+        network.run_simulation();
+        results.push(network.state.arrival_times);
+    }
+    assert_eq!(results[0], results[1]);
+    results.pop().unwrap()
+}
+
+#[test]
+fn test_increasing_transit_time(){
+    assert_eq!(arrival_times(vec![(0, 1), (2, 4)], &[0, 1, 2, 3]), vec![Some(1), Some(2), Some(6), Some(7)]);
+}
+
+#[test]
+fn test_decreasing_transit_time_stays_fifo(){
+    // packets entering from time 2 on would be ready at 3 and 4 but cannot overtake the earlier ones
+    assert_eq!(arrival_times(vec![(0, 5), (2, 1)], &[0, 1, 2, 3]), vec![Some(5), Some(6), Some(6), Some(6)]);
+    assert_eq!(arrival_times(vec![(0, 5), (2, 1)], &[0, 1, 2, 7]), vec![Some(5), Some(6), Some(6), Some(8)]);
+}
+
+#[test]
+fn test_transit_time_schedule_from_json(){
+    let transit_time_val = serde_json::json!([{"from": 0, "transit_time": 2}, {"from": 30, "transit_time": 5}]);
+    assert_eq!(read_json::get_transit_time_schedule(&transit_time_val), Schedule::new(vec![(0, 2), (30, 5)]));
+    assert_eq!(read_json::get_transit_time_schedule(&serde_json::json!(3)), Schedule::constant(3));
+}
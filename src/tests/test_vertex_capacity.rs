@@ -0,0 +1,108 @@
+// This file contains tests for limits on the number of packets passing a vertex
+#[cfg(test)]
+use {
+    crate::builder::NetworkBuilder,
+    crate::fraction::Fraction,
+    crate::network::SimulationConfig,
+    crate::read_json,
+};
+
+#[test]
+fn test_vertex_capacity_at_merge(){
+    for event_driven in [false, true]{
+        // one packet per step passes v, the zipper distributes the passages over both incoming edges
+        let (mut network, _) = NetworkBuilder::new()
+            .edge("u", "v", 1, Fraction::new(2, 1))
+            .edge("w", "v", 1, Fraction::new(2, 1))
+            .edge("v", "t", 1, Fraction::new(4, 1))
+            .vertex_capacity("v", Fraction::new(1, 1))
+            .packet(0, 0, &["u", "v", "t"])
+            .packet(0, 0, &["u", "v", "t"])
+            .packet(1, 0, &["w", "v", "t"])
+            .packet(1, 0, &["w", "v", "t"])
+            .config(SimulationConfig{event_driven, ..Default::default()})
+            .build();
+        assert_eq!(network.run_simulation(), None);
+        assert_eq!(network.state.entrance_times, vec![Some(1), Some(3), Some(2), Some(4)]);
+    }
+}
+
+#[test]
+fn test_vertex_capacity_over_outgoing_edges(){
+    // the capacity of v is shared by both outgoing edges
+    let (mut network, _) = NetworkBuilder::new()
+        .edge("u", "v", 1, Fraction::new(4, 1))
+        .edge("v", "a", 1, Fraction::new(4, 1))
+        .edge("v", "b", 1, Fraction::new(4, 1))
+        .vertex_capacity("v", Fraction::new(2, 1))
+        .packet(0, 0, &["u", "v", "a"])
+        .packet(0, 0, &["u", "v", "b"])
+        .packet(0, 0, &["u", "v", "a"])
+        .packet(0, 0, &["u", "v", "b"])
+        .build();
+    network.run_simulation();
+    assert_eq!(network.state.arrival_times, vec![Some(2), Some(2), Some(3), Some(3)]);
+}
+
+#[test]
+fn test_fractional_vertex_capacity_for_released_packets(){
+    let (mut network, _) = NetworkBuilder::new()
+        .edge("s", "t", 1, Fraction::new(3, 1))
+        .vertex_capacity("s", Fraction::new(1, 2))
+        .packet(0, 0, &["s", "t"])
+        .packet(0, 0, &["s", "t"])
+        .packet(0, 0, &["s", "t"])
+        .build();
+    network.run_simulation();
+    assert_eq!(network.state.entrance_times, vec![Some(1), Some(3), Some(5)]);
+}
+
+#[test]
+fn test_vertex_capacity_from_json(){
+    let network_val = serde_json::json!({
+        "edges": [
+            {"v_from": "s", "v_to": "t", "transit_time": 1, "capacity": {"numerator": 1, "denominator": 1}},
+        ],
+        "vertex_capacities": {"t": {"numerator": 3, "denominator": 2}},
+    });
+    let (vertex_name_to_id, _) = read_json::map_vertex_name_id(&network_val);
+    let (_, vertices, _) = read_json::get_network(&network_val, &vertex_name_to_id);
+    assert_eq!(vertices[0].capacity, None);
+    assert_eq!(vertices[1].capacity, Some(Fraction::new(3, 2)));
+}
+
+#[test]
+fn test_vertex_capacity_with_full_edge(){
+    for event_driven in [false, true]{
+        // packet 1 waits for the full edge v -> a without using the capacity of v, so packet 2 passes v behind it
+        let (mut network, _) = NetworkBuilder::new()
+            .edge("v", "a", 3, Fraction::new(1, 1))
+            .storage(1)
+            .edge("v", "b", 1, Fraction::new(1, 1))
+            .vertex_capacity("v", Fraction::new(1, 1))
+            .packet(0, 0, &["v", "a"])
+            .packet(0, 0, &["v", "a"])
+            .packet(0, 0, &["v", "b"])
+            .config(SimulationConfig{event_driven, ..Default::default()})
+            .build();
+        assert_eq!(network.run_simulation(), None);
+        assert_eq!(network.state.entrance_times, vec![Some(0), Some(4), Some(2)]);
+    }
+}
+
+#[test]
+fn test_vertex_capacity_behind_slow_edge(){
+    for event_driven in [false, true]{
+        // the packet waits at a after s -> a served it, so the credit of a grows until it passes
+        let (mut network, _) = NetworkBuilder::new()
+            .edge("s", "a", 1, Fraction::new(1, 2))
+            .edge("a", "t", 1, Fraction::new(1, 1))
+            .vertex_capacity("a", Fraction::new(1, 2))
+            .packet(0, 0, &["s", "a", "t"])
+            .config(SimulationConfig{event_driven, time_horizon : Some(200), ..Default::default()})
+            .build();
+        assert_eq!(network.run_simulation(), None);
+        assert_eq!(network.state.entrance_times, vec![Some(3)]);
+        assert_eq!(network.state.arrival_times, vec![Some(4)]);
+    }
+}
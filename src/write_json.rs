@@ -4,20 +4,127 @@ use serde_json::json;
 use serde_json::to_string_pretty;
 
 // use std::io::{BufWriter, stdout, Write};
-use crate::network::{self, CommodityId, VertexId, Time};
+use crate::deadlock::DeadlockReport;
+use crate::equilibrium::EquilibriumResult;
+use crate::itinerary::EdgeVisit;
+use crate::queue_lengths::QueueSample;
+use crate::statistics;
+use crate::network::{self, Topology, SimulationState, CommodityId, VertexId, EdgeId, PacketId, Time};
 
-pub fn get_output_val(network : &network::Network, vertex_id_to_name : HashMap<VertexId, String>) -> serde_json::Value{
-    assert!(vertex_id_to_name.len() == network.vertices.len());
+// Names of the vertices on the path of the packet, only the source if it has not chosen an edge yet
+fn vertex_path<'a>(topology : &Topology, state : &SimulationState, packet_id : PacketId, vertex_id_to_name : &'a HashMap<VertexId, String>) -> Vec<&'a str>{
+    let path = state.path(topology, packet_id);
+    std::iter::once(topology.packets[packet_id].source)
+        .chain(path.iter().map(|edge_id| topology.edges[*edge_id].v_to))
+        .map(|vertex_id| vertex_id_to_name[&vertex_id].as_str())
+        .collect()
+}
+
+pub fn get_output_val(topology : &Topology, state : &SimulationState, vertex_id_to_name : HashMap<VertexId, String>) -> serde_json::Value{
+    assert!(vertex_id_to_name.len() == topology.vertices.len());
+    // dropped packets have no arrival and travel time
+    let dropped_packets : Vec<serde_json::Value> = state.drops.iter().enumerate().filter_map(|(packet_id, drop)| drop.map(|(time, edge_id)| json!({
+        "packet_id": packet_id,
+        "time": time,
+        "v_from": vertex_id_to_name[&topology.edges[edge_id].v_from],
+        "v_to": vertex_id_to_name[&topology.edges[edge_id].v_to],
+    }))).collect();
+    let class_statistics = statistics::class_statistics(topology, state);
+    // packets still on an edge if the simulation stopped at the time horizon
+    let in_flight_packets : Vec<serde_json::Value> = statistics::in_flight_packets(topology, state).iter().map(|in_flight_packet| json!({
+        "packet_id": in_flight_packet.packet_id,
+        "v_from": vertex_id_to_name[&topology.edges[in_flight_packet.edge_id].v_from],
+        "v_to": vertex_id_to_name[&topology.edges[in_flight_packet.edge_id].v_to],
+        "path_position": in_flight_packet.path_position,
+    })).collect();
+    let summary = statistics::summary(topology, state);
     json!({
-        "commodity_ids": (0..network.packets.len()).map(|i| network.packets[i].commodity_id).collect::<Vec<CommodityId>>(),
-        "arrival_times": network.arrival_times,
-        "travel_times": (0..network.packets.len()).map(|i| network.arrival_times[i].unwrap() - network.packets[i].release_time).collect::<Vec<Time>>(),
+        "commodity_ids": (0..topology.packets.len()).map(|i| topology.packets[i].commodity_id).collect::<Vec<CommodityId>>(),
+        "arrival_times": state.arrival_times,
+        "travel_times": (0..topology.packets.len()).map(|i| state.arrival_times[i].map(|arrival_time| arrival_time - topology.packets[i].release_time)).collect::<Vec<Option<Time>>>(),
+        "dropped_packets": dropped_packets,
+        // given paths and the paths realized by adaptively routed packets
+        "paths": (0..topology.packets.len()).map(|packet_id| vertex_path(topology, state, packet_id, &vertex_id_to_name)).collect::<Vec<Vec<&str>>>(),
+        "priority_classes": topology.packets.iter().map(|packet| packet.priority_class).collect::<Vec<usize>>(),
+        "class_statistics": class_statistics.iter().map(|class_statistics| json!({
+            "priority_class": class_statistics.priority_class,
+            "n_packets": class_statistics.n_packets,
+            "n_arrived": class_statistics.n_arrived,
+            "n_dropped": class_statistics.n_dropped,
+            "mean_travel_time": class_statistics.mean_travel_time,
+            "max_travel_time": class_statistics.max_travel_time,
+        })).collect::<Vec<serde_json::Value>>(),
+        "in_flight_packets": in_flight_packets,
+        "summary": {
+            "time": summary.time,
+            "n_packets": summary.n_packets,
+            "n_arrived": summary.n_arrived,
+            "n_dropped": summary.n_dropped,
+            "n_pending": summary.n_pending(),
+            "n_in_flight": summary.n_in_flight,
+            "n_waiting": summary.n_waiting,
+            "n_unreleased": summary.n_unreleased,
+        },
     })
 }
 
+// Packets which can never move again and the edges and vertices involved, see DeadlockReport
+pub fn get_deadlock_val(topology : &Topology, report : &DeadlockReport, vertex_id_to_name : &HashMap<VertexId, String>) -> serde_json::Value{
+    let edge_val = |edge_id : &EdgeId| json!({
+        "v_from": vertex_id_to_name[&topology.edges[*edge_id].v_from],
+        "v_to": vertex_id_to_name[&topology.edges[*edge_id].v_to],
+    });
+    json!({
+        "time": report.time,
+        "stuck_packets": report.stuck_packets.iter().map(|stuck_packet| json!({
+            "packet_id": stuck_packet.packet_id,
+            // None if the packet waits to enter the network
+            "edge": stuck_packet.edge_id.as_ref().map(edge_val),
+            "vertex": vertex_id_to_name[&stuck_packet.vertex_id],
+            "reason": stuck_packet.reason.to_string(),
+        })).collect::<Vec<serde_json::Value>>(),
+        "edges": report.edges.iter().map(edge_val).collect::<Vec<serde_json::Value>>(),
+        "vertices": report.vertices.iter().map(|vertex_id| vertex_id_to_name[vertex_id].as_str()).collect::<Vec<&str>>(),
+        "waiting_cycle": report.waiting_cycle.iter().map(edge_val).collect::<Vec<serde_json::Value>>(),
+    })
+}
 
-pub fn write_json(network : &network::Network, vertex_id_to_name : HashMap<VertexId, String>, results_fname : &str){
-    let output_val = get_output_val(network, vertex_id_to_name);
+pub fn get_equilibrium_val(result : &EquilibriumResult) -> serde_json::Value{
+    json!({
+        "converged": result.converged,
+        "rounds": result.rounds,
+        "path_changes": result.path_changes,
+    })
+}
+
+// For every packet its visited edges with entrance, eligible and leaving times, see ItineraryRecorder
+pub fn get_itineraries_val(topology : &Topology, itineraries : &[Vec<EdgeVisit>], vertex_id_to_name : &HashMap<VertexId, String>) -> serde_json::Value{
+    itineraries.iter().map(|itinerary| itinerary.iter().map(|visit| json!({
+        "v_from": vertex_id_to_name[&topology.edges[visit.edge_id].v_from],
+        "v_to": vertex_id_to_name[&topology.edges[visit.edge_id].v_to],
+        "entrance_time": visit.entrance_time,
+        "eligible_time": visit.eligible_time,
+        "leaving_time": visit.leaving_time,
+    })).collect::<Vec<serde_json::Value>>()).collect::<Vec<Vec<serde_json::Value>>>().into()
+}
+
+// For every edge its time series of queued packets and packets on the edge, see QueueLengthRecorder
+pub fn get_queue_lengths_val(topology : &Topology, series : &[Vec<QueueSample>], vertex_id_to_name : &HashMap<VertexId, String>) -> serde_json::Value{
+    series.iter().enumerate().map(|(edge_id, edge_series)| json!({
+        "v_from": vertex_id_to_name[&topology.edges[edge_id].v_from],
+        "v_to": vertex_id_to_name[&topology.edges[edge_id].v_to],
+        "times": edge_series.iter().map(|sample| sample.time).collect::<Vec<Time>>(),
+        "n_queued": edge_series.iter().map(|sample| sample.n_queued).collect::<Vec<usize>>(),
+        "n_on_edge": edge_series.iter().map(|sample| sample.n_on_edge).collect::<Vec<usize>>(),
+    })).collect::<Vec<serde_json::Value>>().into()
+}
+
+pub fn write_output_val(output_val : &serde_json::Value, results_fname : &str){
     let mut results_json = File::create(results_fname).unwrap();
-    results_json.write_all(to_string_pretty(&output_val).unwrap().as_bytes()).expect("Failed to write results to json");
-}
\ No newline at end of file
+    results_json.write_all(to_string_pretty(output_val).unwrap().as_bytes()).expect("Failed to write results to json");
+}
+
+pub fn write_json(network : &network::Network, vertex_id_to_name : HashMap<VertexId, String>, results_fname : &str){
+    let output_val = get_output_val(&network.topology, &network.state, vertex_id_to_name);
+    write_output_val(&output_val, results_fname);
+}